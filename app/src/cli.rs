@@ -0,0 +1,192 @@
+use anyhow::{Result, bail};
+use clap::{Parser, ValueEnum};
+use core::Topology;
+use debate_engine::TopologyConfig;
+use std::path::PathBuf;
+
+// command line options, the defaults reproduce the original example.
+// doc comments on the fields are the --help text
+#[derive(Debug, Parser)]
+#[command(about = "Simulate how LLM agents infect each other through debate")]
+pub struct Args {
+    /// Load the whole setup from a toml file instead, the other flags are ignored
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    /// Debate proposition
+    #[arg(long, default_value = "Does pineapple belong on pizza")]
+    pub topic: String,
+
+    /// How agents are connected
+    #[arg(long, value_enum, default_value_t = TopologyKind::Star)]
+    pub topology: TopologyKind,
+
+    /// Hub agent of a star topology
+    #[arg(long, default_value_t = 2)]
+    pub center: u32,
+
+    /// Back and forth exchanges per debate
+    #[arg(long, default_value_t = 2)]
+    pub turns: usize,
+
+    /// Model judging every debate
+    #[arg(long, default_value = "gpt-5.2-chat-latest")]
+    pub judge: String,
+
+    /// One model per agent, comma separated, agent ids follow the order
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_value = "gpt-3.5-turbo,gpt-3.5-turbo,gpt-5.2-chat-latest,gpt-3.5-turbo,gpt-3.5-turbo,gpt-3.5-turbo"
+    )]
+    pub models: Vec<String>,
+
+    /// Agent infected at the start
+    #[arg(long, default_value_t = 2)]
+    pub patient_zero: u32,
+
+    /// Debates run at once
+    #[arg(long, default_value_t = 5)]
+    pub parallelism: usize,
+
+    /// Connection probability of a random topology
+    #[arg(long, default_value_t = 0.5)]
+    pub probability: f64,
+
+    /// Nearest neighbors per agent in a small world topology
+    #[arg(long, default_value_t = 2)]
+    pub k: usize,
+
+    /// Rewiring probability of a small world topology
+    #[arg(long, default_value_t = 0.2)]
+    pub beta: f64,
+
+    /// Edges per new agent in a scale free topology
+    #[arg(long, default_value_t = 2)]
+    pub m: usize,
+
+    /// Grid and torus rows, rows * cols must match the number of models
+    #[arg(long)]
+    pub rows: Option<usize>,
+    /// Grid and torus columns
+    #[arg(long)]
+    pub cols: Option<usize>,
+
+    /// Makes random topologies and debate order reproducible
+    #[arg(long)]
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TopologyKind {
+    FullyConnected,
+    Ring,
+    Star,
+    Random,
+    SmallWorld,
+    ScaleFree,
+    Grid,
+    Torus,
+}
+
+impl Args {
+    // ids 0..models.len(), checked against the options that refer to agents
+    pub fn agent_ids(&self) -> Result<Vec<u32>> {
+        if self.models.is_empty() {
+            bail!("--models needs at least one model");
+        }
+        let count = self.models.len() as u32;
+        if self.patient_zero >= count {
+            bail!(
+                "--patient-zero {} is not an agent, --models gives agents 0 to {}",
+                self.patient_zero,
+                count - 1
+            );
+        }
+        if self.topology == TopologyKind::Star && self.center >= count {
+            bail!(
+                "--center {} is not an agent, --models gives agents 0 to {}",
+                self.center,
+                count - 1
+            );
+        }
+        Ok((0..count).collect())
+    }
+
+    pub fn build_topology(&self, agent_ids: &[u32]) -> Result<Topology> {
+        let config = match self.topology {
+            TopologyKind::FullyConnected => TopologyConfig::FullyConnected,
+            TopologyKind::Ring => TopologyConfig::Ring,
+            TopologyKind::Star => TopologyConfig::Star {
+                center: self.center,
+            },
+            TopologyKind::Random => TopologyConfig::Random {
+                probability: self.probability,
+            },
+            TopologyKind::SmallWorld => TopologyConfig::SmallWorld {
+                k: self.k,
+                beta: self.beta,
+            },
+            TopologyKind::ScaleFree => TopologyConfig::ScaleFree { m: self.m },
+            TopologyKind::Grid | TopologyKind::Torus => {
+                let (Some(rows), Some(cols)) = (self.rows, self.cols) else {
+                    bail!("--topology grid and torus need --rows and --cols");
+                };
+                if self.topology == TopologyKind::Grid {
+                    TopologyConfig::Grid { rows, cols }
+                } else {
+                    TopologyConfig::Torus { rows, cols }
+                }
+            }
+        };
+        config.build(agent_ids, self.seed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::TopologyBuilder;
+
+    fn parse(args: &[&str]) -> Args {
+        Args::try_parse_from(std::iter::once("app").chain(args.iter().copied())).unwrap()
+    }
+
+    #[test]
+    fn test_defaults_match_the_example() {
+        let args = parse(&[]);
+        let ids = args.agent_ids().unwrap();
+
+        assert_eq!(ids, vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(
+            args.build_topology(&ids).unwrap(),
+            TopologyBuilder::star(2, &[0, 1, 3, 4, 5])
+        );
+    }
+
+    #[test]
+    fn test_counts_are_validated_against_models() {
+        let args = parse(&["--models", "a,b,c", "--center", "3"]);
+        assert!(args.agent_ids().is_err());
+
+        let args = parse(&["--models", "a,b,c", "--patient-zero", "0", "--center", "1"]);
+        assert!(args.agent_ids().is_ok());
+
+        let args = parse(&["--models", "a,b,c", "--topology", "grid", "--rows", "2"]);
+        assert!(args.build_topology(&[0, 1, 2]).is_err());
+
+        let args = parse(&[
+            "--models",
+            "a,b,c",
+            "--topology",
+            "grid",
+            "--rows",
+            "2",
+            "--cols",
+            "2",
+        ]);
+        assert!(args.build_topology(&[0, 1, 2]).is_err());
+
+        assert!(Args::try_parse_from(["app", "--topology", "hexagon"]).is_err());
+    }
+}
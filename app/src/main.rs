@@ -1,8 +1,13 @@
 use anyhow::Result;
-use core::{InfectionStatus, Registry, TopologyBuilder};
-use debate_engine::Simulation;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
+use cli::Args;
+use core::Registry;
+use debate_engine::{Simulation, SimulationConfig};
 use dotenv::dotenv;
-use visualizer::visualize_graph;
+use visualizer::{ColorScheme, visualize_graph};
+
+mod cli;
 
 /// Debate Simulation - AI Agent Network
 ///
@@ -15,12 +20,84 @@ use visualizer::visualize_graph;
 /// Requirements:
 /// - Create a `.env` file with your API keys (see .env.example)
 /// - Set OPENAI_API_KEY or other provider keys for the genai crate
+///
+/// Everything below can be changed from the command line, see `cargo run -- --help`, e.g.
+/// cargo run -- --topic "Is AI safety solved" --topology ring --turns 3 --models gpt-4,gpt-4,llama3
+/// or described in a toml file, see SimulationConfig: cargo run -- --config experiment.toml
 
 #[tokio::main]
 async fn main() -> Result<()> {
     // Load environment variables from .env file
     dotenv().ok();
 
+    let args = Args::parse();
+
+    // a config file describes the whole experiment, the other flags are ignored then
+    let (mut registry, sim) = match &args.config {
+        Some(path) => SimulationConfig::from_toml_file(path)?.build()?,
+        None => setup_from_flags(&args)?,
+    };
+
+    let result = sim.run(&mut registry).await?;
+
+    // ============================================================================
+    // Step 6: Display Results
+    // ============================================================================
+    println!("Simulation Statistics:");
+    println!("  Total Agents:    {}", result.total_agents);
+    println!("  Total Debates:   {}", result.rounds);
+    println!("  Timed out:       {}", result.timed_out.len());
+    println!("  API calls:       {}", result.api_calls);
+    println!("  Wall time:       {:.1?}", result.wall_time);
+    println!("  Stopped because: {:?}", result.terminated_reason);
+    println!("  R0:              {:.2}", result.r0());
+    println!(
+        "  Tokens used:     {} ({} prompt, {} completion)",
+        result.total_tokens(),
+        result.usage.prompt_tokens,
+        result.usage.completion_tokens
+    );
+    println!(
+        "  Infected count:  {} ({:.1}%)",
+        result.infected,
+        result.infection_rate() * 100.0
+    );
+    println!(
+        "  Immune count: {} ({:.1}%)",
+        result.immune,
+        result.immunity_rate() * 100.0
+    );
+    println!(
+        "  Healthy count: {} ({:.1}%)",
+        result.healthy,
+        result.healthy_rate() * 100.0
+    );
+
+    for agent in registry.get_all_agents() {
+        println!("{}", agent);
+    }
+
+    // ============================================================================
+    // Step 7: Generate graph
+    // ============================================================================
+
+    visualize_graph(&registry, ColorScheme::Infection)?;
+
+    Ok(())
+}
+
+// steps 1 to 5 of the example, every value comes from a flag
+fn setup_from_flags(args: &Args) -> Result<(Registry, Simulation)> {
+    // bad combinations (e.g. a center that isnt an agent) are reported like parse errors
+    let topology = args
+        .agent_ids()
+        .and_then(|ids| args.build_topology(&ids))
+        .unwrap_or_else(|err| {
+            Args::command()
+                .error(ErrorKind::ValueValidation, err)
+                .exit()
+        });
+
     // ============================================================================
     // Step 1: Create Registry (Agent Manager)
     // ============================================================================
@@ -33,19 +110,22 @@ async fn main() -> Result<()> {
     // Available topology types:
     //
     // - Fully Connected: Every agent connected to every other agent
-    //   TopologyBuilder::fully_connected(&[0, 1, 2, 3, 4, 5])
+    //   --topology fully-connected
     //
     // - Ring: Agents form a circular chain
-    //   TopologyBuilder::ring(&[0, 1, 2, 3, 4, 5])
+    //   --topology ring
     //
     // - Star: One central agent connected to all peripheral agents
-    //   TopologyBuilder::star(2, &[0, 1, 3, 4, 5])
+    //   --topology star --center 2
     //
     // - Random: Probabilistic connections between agents
-    //   TopologyBuilder::random(&[0, 1, 2, 3, 4, 5], 0.5)
+    //   --topology random --probability 0.5
+    //
+    // - Small world, scale free, grid and torus
+    //   --topology small-world --k 2 --beta 0.2, --topology scale-free --m 2,
+    //   --topology grid --rows 2 --cols 3
 
-    let agent_ids = vec![0, 1, 2, 3, 4, 5];
-    registry.topology = Some(TopologyBuilder::star(2, &agent_ids));
+    registry.topology = Some(topology);
 
     // ============================================================================
     // Step 3: Create Agents with AI Models
@@ -57,18 +137,12 @@ async fn main() -> Result<()> {
     // - OpenAI: "gpt-5.2-chat-latest", "gpt-3.5-turbo", etc.
     // - Anthropic: "claude-3-opus-20240229", etc.
     // - Other providers supported by genai crate
+    //
+    // --models takes one model per agent, agent 0 first. The default gives the star center
+    // (agent 2) the stronger model.
 
-    let agent_models = vec![
-        "gpt-3.5-turbo",       // Agent 0
-        "gpt-3.5-turbo",       // Agent 1
-        "gpt-5.2-chat-latest", // Agent 2 (center - stronger model)
-        "gpt-3.5-turbo",       // Agent 3
-        "gpt-3.5-turbo",       // Agent 4
-        "gpt-3.5-turbo",       // Agent 5
-    ];
-
-    for model in agent_models {
-        registry.create_agent(model.to_string());
+    for model in &args.models {
+        registry.create_agent(model.clone());
     }
 
     // ============================================================================
@@ -78,8 +152,7 @@ async fn main() -> Result<()> {
     // This agent will attempt to convince connected healthy agents.
     // Once a healthy agents becomes infected, it will start infecting as well.
 
-    let patient_zero = 2;
-    registry.infect_patient_init(patient_zero)?;
+    registry.infect_patient_init(args.patient_zero)?;
 
     // ============================================================================
     // Step 5: Configure and Run Simulation
@@ -91,67 +164,16 @@ async fn main() -> Result<()> {
     //
     // Simulation methods
     // - .with_parallelism(usize): Optionally set batch size to run asynchronously
+    // - .with_debate_timeout(Duration): Optionally skip debates that take too long
+    // - .with_max_rounds(usize): Optionally stop after this many debates
+    // - .with_max_api_calls(usize): Optionally cap the number of LLM requests
     // - .run(&mut Registry): Run the simulation
 
-    let topic = "Does pineapple belong on pizza";
-    let turns_per_debate = 2;
-    let judge_model = "gpt-5.2-chat-latest";
-    let max_parallel_debates = 5;
-
-    let sim = Simulation::new(topic, turns_per_debate, judge_model)
-        .with_parallelism(max_parallel_debates);
-
-    let result = sim.run(&mut registry).await?;
-
-    // ============================================================================
-    // Step 6: Display Results
-    // ============================================================================
-    println!("Simulation Statistics:");
-    println!("  Total Agents:    {}", result.total_agents);
-    println!("  Total Debates:   {}", result.rounds);
-    println!(
-        "  Infected count:  {} ({:.1}%)",
-        result.infected,
-        result.infection_rate() * 100.0
-    );
-    println!(
-        "  Immune count: {} ({:.1}%)",
-        result.immune,
-        result.immunity_rate() * 100.0
-    );
-    println!(
-        "  Healthy count: {} ({:.1}%)",
-        result.immune,
-        result.healthy_rate() * 100.0
-    );
-
-    for id in registry.get_all_agent_ids() {
-        let agent = registry.get_agent(id).unwrap();
-
-        let status_display = match agent.infection_status {
-            InfectionStatus::Healthy => " Healthy",
-            InfectionStatus::Infected => " Infected",
-            InfectionStatus::Immune => " Immune",
-        };
-
-        let infected_by = agent
-            .infected_by
-            .map(|pid| format!("Agent {}", pid))
-            .unwrap_or_else(|| "-".to_string());
-
-        let model_display = agent.model.clone();
-
-        println!(
-            "{} {} {} {}",
-            id, status_display, model_display, infected_by
-        );
+    let mut sim =
+        Simulation::new(&args.topic, args.turns, &args.judge).with_parallelism(args.parallelism);
+    if let Some(seed) = args.seed {
+        sim = sim.with_seed(seed);
     }
 
-    // ============================================================================
-    // Step 7: Generate graph
-    // ============================================================================
-
-    visualize_graph(&registry)?;
-
-    Ok(())
+    Ok((registry, sim))
 }
@@ -1,4 +1,8 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+use crate::debate::Debate;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum InfectionStatus {
     #[default]
     Healthy, // hasnt debated
@@ -6,13 +10,57 @@ pub enum InfectionStatus {
     Immune,   // won debate
 }
 
-#[derive(Debug, Clone)]
+impl fmt::Display for InfectionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InfectionStatus::Healthy => write!(f, "Healthy"),
+            InfectionStatus::Infected => write!(f, "Infected"),
+            InfectionStatus::Immune => write!(f, "Immune"),
+        }
+    }
+}
+
+// sampling settings sent with every request an agent makes, none keeps the provider default
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct GenerationParams {
+    pub temperature: Option<f64>,
+    pub max_tokens: Option<u32>,
+}
+
+impl GenerationParams {
+    pub fn with_temperature(mut self, temperature: f64) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Agent {
     pub id: u32,
     // ai model agent uses todo!
     pub model: String,
+    // e.g. a hot aggressive debater vs a measured one
+    #[serde(default)]
+    pub gen_params: GenerationParams,
+    // e.g. "aggressive contrarian", added to the debate system prompt when set
+    #[serde(default)]
+    pub persona: Option<String>,
     pub infection_status: InfectionStatus,
     pub infected_by: Option<u32>,
+    // immune from the start rather than by winning a debate, never recovers
+    #[serde(default)]
+    pub vaccinated: bool,
+    // batches spent in the current status, drives sirs recovery
+    #[serde(default)]
+    pub rounds_in_state: usize,
+    // every debate this agent took part in, owned so agents and debates serialize together
+    #[serde(default)]
+    pub debate_history: Vec<Debate>,
 }
 
 impl Agent {
@@ -20,11 +68,30 @@ impl Agent {
         Self {
             id,
             model,
+            gen_params: GenerationParams::default(),
+            persona: None,
             infection_status: InfectionStatus::default(),
             infected_by: None,
+            vaccinated: false,
+            rounds_in_state: 0,
+            debate_history: Vec::new(),
         }
     }
 
+    pub fn with_gen_params(mut self, gen_params: GenerationParams) -> Self {
+        self.gen_params = gen_params;
+        self
+    }
+
+    pub fn with_persona(mut self, persona: impl Into<String>) -> Self {
+        self.persona = Some(persona.into());
+        self
+    }
+
+    pub fn add_debate(&mut self, debate: Debate) {
+        self.debate_history.push(debate);
+    }
+
     pub fn is_infected(&self) -> bool {
         self.infection_status == InfectionStatus::Infected
     }
@@ -38,6 +105,17 @@ impl Agent {
     }
 }
 
+// one row per agent: id, status, model and who infected it ("-" for nobody)
+impl fmt::Display for Agent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {} ", self.id, self.infection_status, self.model)?;
+        match self.infected_by {
+            Some(infector) => write!(f, "Agent {}", infector),
+            None => write!(f, "-"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -53,8 +131,28 @@ mod tests {
         assert_eq!(agent.model, "model");
     }
 
+    #[test]
+    fn test_add_debate() {
+        let mut agent = Agent::new(0, "model".to_string());
+        agent.add_debate(Debate::new(0, 1, 2));
+        agent.add_debate(Debate::new(2, 0, 2));
+
+        assert_eq!(agent.debate_history.len(), 2);
+        assert_eq!(agent.debate_history[1].proposer_id, 2);
+    }
+
     #[test]
     fn test_infection_status_default() {
         assert_eq!(InfectionStatus::default(), InfectionStatus::Healthy);
     }
+
+    #[test]
+    fn test_display() {
+        let mut agent = Agent::new(3, "gpt-4".to_string());
+        assert_eq!(agent.to_string(), "3 Healthy gpt-4 -");
+
+        agent.infection_status = InfectionStatus::Infected;
+        agent.infected_by = Some(7);
+        assert_eq!(agent.to_string(), "3 Infected gpt-4 Agent 7");
+    }
 }
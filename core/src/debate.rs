@@ -1,21 +1,39 @@
+use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::ops::AddAssign;
+
+// who wrote a message, so it still makes sense outside its exchange
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum Role {
+    Proposer,
+    Opposer,
+    Judge,
+    // also what messages saved before roles existed load as
+    #[default]
+    System,
+}
 
 // each individual message
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Message {
     pub id: u32,
+    #[serde(default)]
+    pub role: Role,
     pub message: String,
+    // time the model took to answer, retries included
+    #[serde(default)]
+    pub latency_ms: u64,
 }
 
 // each exchange in debate
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Exchange {
     // message struct proposer message, opposer reply
     pub proposer: Message,
     pub opposer: Message,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum DebateOutcome {
     #[default]
     Ongoing,
@@ -33,11 +51,58 @@ impl fmt::Display for DebateOutcome {
     }
 }
 
-// full debate between both agents, both agents will ref this in their structs
-#[derive(Debug, Clone, PartialEq)]
+// tokens reported by the provider, summed over every request they cover
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+}
+
+impl TokenUsage {
+    pub fn new(prompt_tokens: u64, completion_tokens: u64) -> Self {
+        Self {
+            prompt_tokens,
+            completion_tokens,
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+}
+
+impl AddAssign for TokenUsage {
+    fn add_assign(&mut self, other: Self) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+    }
+}
+
+// one judge's verdict when several judges vote on a debate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JudgeVote {
+    pub judge_model: String,
+    pub outcome: DebateOutcome,
+    pub rationale: Option<String>,
+    pub confidence: Option<f32>,
+}
+
+// full debate between both agents, both agents keep a copy in their history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Debate {
     pub proposer_id: u32,
     pub opposer_id: u32,
+    // proposition being debated, empty for debates recorded before it was kept
+    #[serde(default)]
+    pub topic: String,
+    // models the agents debated with, empty for debates recorded before they were kept
+    #[serde(default)]
+    pub proposer_model: String,
+    #[serde(default)]
+    pub opposer_model: String,
+    // the opposer made the opening argument of each round instead of the proposer
+    #[serde(default)]
+    pub opposer_opened: bool,
 
     // max turns for each agent
     pub max_turns: usize,
@@ -45,6 +110,18 @@ pub struct Debate {
 
     // judges outcome of debate
     pub outcome: DebateOutcome,
+    // judges reasoning and confidence (0.0 - 1.0), none if the judge didnt provide them
+    pub rationale: Option<String>,
+    pub confidence: Option<f32>,
+    // every judge's individual verdict, outcome is the majority of these
+    #[serde(default)]
+    pub votes: Vec<JudgeVote>,
+    // condensed cases the judges read instead of the exchanges, see summarize_before_judge
+    #[serde(default)]
+    pub summary: Option<String>,
+    // debaters, summarizer and judges combined
+    #[serde(default)]
+    pub usage: TokenUsage,
 }
 
 impl Debate {
@@ -52,12 +129,36 @@ impl Debate {
         Self {
             proposer_id,
             opposer_id,
+            topic: String::new(),
+            proposer_model: String::new(),
+            opposer_model: String::new(),
+            opposer_opened: false,
             max_turns,
             exchanges: Vec::new(),
             outcome: DebateOutcome::default(),
+            rationale: None,
+            confidence: None,
+            votes: Vec::new(),
+            summary: None,
+            usage: TokenUsage::default(),
         }
     }
 
+    pub fn with_topic(mut self, topic: impl Into<String>) -> Self {
+        self.topic = topic.into();
+        self
+    }
+
+    pub fn with_models(
+        mut self,
+        proposer_model: impl Into<String>,
+        opposer_model: impl Into<String>,
+    ) -> Self {
+        self.proposer_model = proposer_model.into();
+        self.opposer_model = opposer_model.into();
+        self
+    }
+
     // add exchange
     pub fn add_exchange(&mut self, exchange: Exchange) {
         self.exchanges.push(exchange);
@@ -77,19 +178,24 @@ impl Debate {
     pub fn format_transcript(&self) -> String {
         // debate info
         let mut transcript = format!(
-            "Debate: Agent {} (Proposer) vs Agent {} (Opposer)\n
-             Max turns per agent: {}\n
-             Status: {:?}\n\n",
-            self.proposer_id, self.opposer_id, self.max_turns, self.outcome
+            "Debate: Agent {} (Proposer) vs Agent {} (Opposer)\n",
+            self.proposer_id, self.opposer_id
         );
+        if !self.topic.is_empty() {
+            transcript.push_str(&format!("Topic: {}\n", self.topic));
+        }
+        transcript.push_str(&format!(
+            "Max turns per agent: {}\n\
+             Status: {:?}\n\n",
+            self.max_turns, self.outcome
+        ));
 
-        // exchanges
+        // exchanges, blank line between rounds
         for (i, turn) in self.exchanges.iter().enumerate() {
             transcript.push_str(&format!(
-                "
-                Round {}\n
-                Agent {} (Proposer) Message: {}\n
-                Agent {} (Opposer) Reply: {}\n\n",
+                "Round {}\n\
+                 Agent {} (Proposer) Message: {}\n\
+                 Agent {} (Opposer) Reply: {}\n\n",
                 i + 1,
                 self.proposer_id,
                 turn.proposer.message,
@@ -100,7 +206,146 @@ impl Debate {
 
         // outcome
         transcript.push_str(&format!("Judge's verdict: {}", self.outcome));
+        if let Some(confidence) = self.confidence {
+            transcript.push_str(&format!(" (confidence {:.2})", confidence));
+        }
+        if let Some(rationale) = &self.rationale {
+            transcript.push_str(&format!("\nJudge's reasoning: {}", rationale));
+        }
 
         transcript
     }
 }
+
+// one round of a group debate, every opposer replies to the same proposer message
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupExchange {
+    pub proposer: Message,
+    // (opposer id, reply) in speaking order
+    pub opposers: Vec<(u32, Message)>,
+}
+
+// one proposer against several opposers in a shared transcript, each opposer gets its own verdict
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GroupDebate {
+    pub proposer_id: u32,
+    pub opposer_ids: Vec<u32>,
+    #[serde(default)]
+    pub topic: String,
+    pub max_turns: usize,
+    pub exchanges: Vec<GroupExchange>,
+    // (opposer id, outcome) in opposer_ids order, empty until judged
+    pub outcomes: Vec<(u32, DebateOutcome)>,
+    #[serde(default)]
+    pub usage: TokenUsage,
+}
+
+impl GroupDebate {
+    pub fn new(proposer_id: u32, opposer_ids: Vec<u32>, max_turns: usize) -> Self {
+        Self {
+            proposer_id,
+            opposer_ids,
+            topic: String::new(),
+            max_turns,
+            exchanges: Vec::new(),
+            outcomes: Vec::new(),
+            usage: TokenUsage::default(),
+        }
+    }
+
+    pub fn outcome_for(&self, opposer_id: u32) -> Option<DebateOutcome> {
+        self.outcomes
+            .iter()
+            .find(|(id, _)| *id == opposer_id)
+            .map(|&(_, outcome)| outcome)
+    }
+
+    // the proposer's messages paired with one opposer's replies, as a one on one debate
+    pub fn exchanges_with(&self, opposer_id: u32) -> Vec<Exchange> {
+        self.exchanges
+            .iter()
+            .filter_map(|exchange| {
+                let (_, reply) = exchange.opposers.iter().find(|(id, _)| *id == opposer_id)?;
+                Some(Exchange {
+                    proposer: exchange.proposer.clone(),
+                    opposer: reply.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exchange(id: u32) -> Exchange {
+        Exchange {
+            proposer: Message {
+                id,
+                role: Role::Proposer,
+                message: "argument".to_string(),
+                latency_ms: 0,
+            },
+            opposer: Message {
+                id: id + 1,
+                role: Role::Opposer,
+                message: "reply".to_string(),
+                latency_ms: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn test_is_complete_empty() {
+        let debate = Debate::new(0, 1, 2);
+        assert!(!debate.is_complete());
+    }
+
+    #[test]
+    fn test_is_complete_at_max_turns() {
+        let mut debate = Debate::new(0, 1, 2);
+
+        debate.add_exchange(exchange(0));
+        assert!(!debate.is_complete());
+
+        debate.add_exchange(exchange(2));
+        assert!(debate.is_complete());
+    }
+
+    #[test]
+    fn test_format_transcript_has_no_indented_lines() {
+        let mut debate = Debate::new(0, 1, 2);
+        debate.add_exchange(exchange(0));
+        debate.add_exchange(exchange(2));
+        debate.set_outcome(DebateOutcome::ProposerWon);
+
+        let transcript = debate.format_transcript();
+
+        assert!(transcript.lines().all(|line| !line.starts_with("  ")));
+        assert!(transcript.starts_with("Debate: Agent 0 (Proposer) vs Agent 1 (Opposer)\n"));
+        assert!(transcript.contains("\n\nRound 2\nAgent 0 (Proposer) Message: argument\n"));
+        assert!(transcript.ends_with("Judge's verdict: Proposer won"));
+        assert!(!transcript.contains("Topic:"));
+    }
+
+    #[test]
+    fn test_format_transcript_includes_topic() {
+        let debate = Debate::new(0, 1, 2).with_topic("tabs over spaces");
+
+        let transcript = debate.format_transcript();
+
+        assert!(transcript.contains("(Opposer)\nTopic: tabs over spaces\nMax turns"));
+    }
+
+    #[test]
+    fn test_message_role_serde() {
+        let json = serde_json::to_string(&exchange(0)).unwrap();
+        assert!(json.contains(r#""role":"Proposer""#));
+        assert!(json.contains(r#""role":"Opposer""#));
+
+        // messages saved before roles existed
+        let old: Message = serde_json::from_str(r#"{"id":3,"message":"hi"}"#).unwrap();
+        assert_eq!(old.role, Role::System);
+    }
+}
@@ -0,0 +1,80 @@
+use std::fmt;
+
+// why a registry operation was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistryError {
+    AgentNotFound(u32),
+    // every missing id of a batch operation
+    AgentsNotFound(Vec<u32>),
+    NotInfected(u32),
+    NotHealthy(u32),
+    NoTopology,
+    NotConnected(u32, u32),
+    // topology ids without a matching agent
+    UnknownTopologyAgents(Vec<u32>),
+    // the agent's state is not what applying the outcome would have left
+    CannotUndo(u32),
+    // a simulation needs at least one infected agent to start from
+    NoInfectedAgents,
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::AgentNotFound(id) => write!(f, "Agent {} not found", id),
+            RegistryError::AgentsNotFound(ids) => write!(f, "Agents {:?} not found", ids),
+            RegistryError::NotInfected(id) => write!(f, "Agent {} is not infected", id),
+            RegistryError::NotHealthy(id) => write!(f, "Agent {} is not healthy", id),
+            RegistryError::NoTopology => write!(f, "Topology does not exist"),
+            RegistryError::NotConnected(a, b) => {
+                write!(f, "Agents {} and {} are not connected", a, b)
+            }
+            RegistryError::UnknownTopologyAgents(ids) => {
+                write!(f, "Topology references agents {:?} that do not exist", ids)
+            }
+            RegistryError::NoInfectedAgents => {
+                write!(f, "No infected agents, infect a patient zero first")
+            }
+            RegistryError::CannotUndo(id) => {
+                write!(f, "Agent {} does not match the outcome to undo", id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+// why a topology builder rejected its input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TopologyError {
+    EmptyAgentList,
+    // which parameter and why, e.g. "beta (1.5) must be between 0.0 and 1.0"
+    ParameterOutOfRange(String),
+    // (expected, actual)
+    WrongAgentCount(usize, usize),
+    // every id listed more than once, sorted
+    DuplicateAgentIds(Vec<u32>),
+}
+
+impl fmt::Display for TopologyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TopologyError::EmptyAgentList => write!(f, "Topology needs at least one agent"),
+            TopologyError::ParameterOutOfRange(reason) => {
+                write!(f, "Topology parameter out of range: {}", reason)
+            }
+            TopologyError::WrongAgentCount(expected, actual) => {
+                write!(
+                    f,
+                    "Topology needs exactly {} agents, got {}",
+                    expected, actual
+                )
+            }
+            TopologyError::DuplicateAgentIds(ids) => {
+                write!(f, "Topology agent ids {:?} are listed more than once", ids)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TopologyError {}
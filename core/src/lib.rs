@@ -1,9 +1,22 @@
 pub mod agent;
 pub mod debate;
+pub mod error;
 pub mod registry;
 pub mod topology;
 
-pub use agent::{Agent, InfectionStatus};
-pub use debate::{Debate, DebateOutcome, Exchange, Message};
-pub use registry::{Registry, RegistryStatistics};
+pub use agent::{Agent, GenerationParams, InfectionStatus};
+pub use debate::{
+    Debate, DebateOutcome, Exchange, GroupDebate, GroupExchange, JudgeVote, Message, Role,
+    TokenUsage,
+};
+pub use error::{RegistryError, TopologyError};
+pub use registry::{Registry, RegistryBuilder, RegistryStatistics};
 pub use topology::{Topology, TopologyBuilder};
+
+// `use core::prelude::*;` covers setting up and inspecting a run
+pub mod prelude {
+    pub use crate::{
+        Agent, Debate, DebateOutcome, Exchange, InfectionStatus, Message, Registry,
+        RegistryBuilder, RegistryStatistics, Topology, TopologyBuilder,
+    };
+}
@@ -1,10 +1,14 @@
 use crate::agent::{Agent, InfectionStatus};
-use crate::debate::DebateOutcome;
+use crate::debate::{Debate, DebateOutcome, GroupDebate};
+use crate::error::RegistryError;
 use crate::topology::Topology;
 use anyhow::Context;
-use std::collections::HashMap;
+#[cfg(feature = "petgraph")]
+use petgraph::stable_graph::StableGraph;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Registry {
     // acts as counter for agent_id
     next_agent_id: u32,
@@ -31,6 +35,66 @@ impl Registry {
         id
     }
 
+    // every topology id must belong to an agent, the error lists the ones that dont, sorted
+    pub fn validate_topology(&self) -> Result<(), RegistryError> {
+        let Some(topology) = &self.topology else {
+            return Ok(());
+        };
+
+        let mut unknown: Vec<u32> = topology
+            .get_all_agent_ids()
+            .into_iter()
+            .filter(|id| !self.agents.contains_key(id))
+            .collect();
+        if unknown.is_empty() {
+            return Ok(());
+        }
+        unknown.sort_unstable();
+        Err(RegistryError::UnknownTopologyAgents(unknown))
+    }
+
+    // one registry per connected component of the topology, agents outside it each get their
+    // own. ids are kept so the parts can be merged back with absorb, ordered by lowest id
+    pub fn split_components(&self) -> Vec<Registry> {
+        let mut components = self
+            .topology
+            .as_ref()
+            .map(|topology| topology.connected_components())
+            .unwrap_or_default();
+        let placed: HashSet<u32> = components.iter().flatten().copied().collect();
+        components.extend(
+            self.get_all_agent_ids()
+                .into_iter()
+                .filter(|id| !placed.contains(id))
+                .map(|id| vec![id]),
+        );
+        components.sort_unstable_by_key(|component| component[0]);
+
+        components
+            .into_iter()
+            .map(|ids| {
+                let id_set: HashSet<u32> = ids.iter().copied().collect();
+                Registry {
+                    next_agent_id: self.next_agent_id,
+                    agents: ids
+                        .iter()
+                        .filter_map(|id| self.agents.get(id))
+                        .map(|agent| (agent.id, agent.clone()))
+                        .collect(),
+                    topology: self
+                        .topology
+                        .as_ref()
+                        .map(|topology| topology.subgraph(&id_set)),
+                }
+            })
+            .collect()
+    }
+
+    // take back the agents of a part from split_components, the topology is left alone
+    pub fn absorb(&mut self, part: Registry) {
+        self.agents.extend(part.agents);
+    }
+
     // get agent (read)
     pub fn get_agent(&self, id: u32) -> Option<&Agent> {
         self.agents.get(&id)
@@ -41,13 +105,25 @@ impl Registry {
         self.agents.get_mut(&id)
     }
 
+    // remove agent and its connections so it cant be targeted anymore
+    pub fn remove_agent(&mut self, id: u32) -> Option<Agent> {
+        let agent = self.agents.remove(&id)?;
+        if let Some(topology) = &mut self.topology {
+            topology.remove_agent(id);
+        }
+        Some(agent)
+    }
+
+    // every id list below is sorted ascending so output doesnt depend on hashmap order
     pub fn get_all_agent_ids(&self) -> Vec<u32> {
-        self.agents.keys().copied().collect()
+        self.ids_where(|_| true)
     }
 
-    // gets all agents in vec (read)
+    // gets all agents in vec (read), sorted by id
     pub fn get_all_agents(&self) -> Vec<&Agent> {
-        self.agents.values().collect()
+        let mut agents: Vec<&Agent> = self.agents.values().collect();
+        agents.sort_unstable_by_key(|agent| agent.id);
+        agents
     }
 
     // agent count not using counter
@@ -69,38 +145,126 @@ impl Registry {
     }
 
     pub fn get_infected_agent_ids(&self) -> Vec<u32> {
-        self.agents
-            .iter()
-            .filter(|(_, agent)| agent.is_infected())
-            .map(|(id, _)| *id)
-            .collect()
+        self.ids_where(Agent::is_infected)
     }
 
     pub fn get_healthy_agent_ids(&self) -> Vec<u32> {
-        self.agents
-            .iter()
-            .filter(|(_, agent)| agent.is_healthy())
-            .map(|(id, _)| *id)
-            .collect()
+        self.ids_where(Agent::is_healthy)
     }
 
     pub fn get_immune_agent_ids(&self) -> Vec<u32> {
+        self.ids_where(Agent::is_immune)
+    }
+
+    // lazy version of the get_*_agent_ids above, in no particular order
+    pub fn iter_by_status(&self, status: InfectionStatus) -> impl Iterator<Item = (u32, &Agent)> {
         self.agents
             .iter()
-            .filter(|(_, agent)| agent.is_immune())
+            .filter(move |(_, agent)| agent.infection_status == status)
+            .map(|(&id, agent)| (id, agent))
+    }
+
+    pub fn get_agent_ids_by_model(&self, model: &str) -> Vec<u32> {
+        self.ids_where(|agent| agent.model == model)
+    }
+
+    fn ids_where(&self, keep: impl Fn(&Agent) -> bool) -> Vec<u32> {
+        let mut ids: Vec<u32> = self
+            .agents
+            .iter()
+            .filter(|(_, agent)| keep(agent))
             .map(|(id, _)| *id)
-            .collect()
+            .collect();
+        ids.sort_unstable();
+        ids
     }
 
     // calls infect_init() for agents who start with the infection
-    pub fn infect_patient_init(&mut self, agent_id: u32) -> anyhow::Result<()> {
-        if let Some(agent) = self.agents.get_mut(&agent_id) {
-            agent.infection_status = crate::agent::InfectionStatus::Infected;
+    pub fn infect_patient_init(&mut self, agent_id: u32) -> Result<(), RegistryError> {
+        let agent = self
+            .agents
+            .get_mut(&agent_id)
+            .ok_or(RegistryError::AgentNotFound(agent_id))?;
+
+        agent.infection_status = InfectionStatus::Infected;
+        agent.infected_by = None;
+        agent.vaccinated = false;
+        agent.rounds_in_state = 0;
+        Ok(())
+    }
+
+    // infect several patient zeros at once, nothing is applied if any id is missing
+    pub fn infect_patients_init(&mut self, agent_ids: &[u32]) -> Result<(), RegistryError> {
+        let missing: Vec<u32> = agent_ids
+            .iter()
+            .copied()
+            .filter(|id| !self.agents.contains_key(id))
+            .collect();
+        if !missing.is_empty() {
+            return Err(RegistryError::AgentsNotFound(missing));
+        }
+
+        for &agent_id in agent_ids {
+            self.infect_patient_init(agent_id)?;
+        }
+        Ok(())
+    }
+
+    // pre-immunize agents before a run, can_debate only targets healthy agents so they
+    // act as firewalls. nothing is applied if any id is missing
+    pub fn vaccinate(&mut self, agent_ids: &[u32]) -> Result<(), RegistryError> {
+        let missing: Vec<u32> = agent_ids
+            .iter()
+            .copied()
+            .filter(|id| !self.agents.contains_key(id))
+            .collect();
+        if !missing.is_empty() {
+            return Err(RegistryError::AgentsNotFound(missing));
+        }
+
+        for agent_id in agent_ids {
+            let agent = self.agents.get_mut(agent_id).unwrap();
+            agent.infection_status = InfectionStatus::Immune;
             agent.infected_by = None;
-            Ok(())
-        } else {
-            anyhow::bail!(format!("Agent {} not found", agent_id))
+            agent.vaccinated = true;
+            agent.rounds_in_state = 0;
         }
+        Ok(())
+    }
+
+    // set every agent back to healthy and forget their debates, topology and models are kept
+    pub fn reset_infections(&mut self) {
+        for agent in self.agents.values_mut() {
+            agent.infection_status = InfectionStatus::Healthy;
+            agent.infected_by = None;
+            agent.vaccinated = false;
+            agent.rounds_in_state = 0;
+            agent.debate_history.clear();
+        }
+    }
+
+    // sirs step, ages every infected/immune agent by one round and sends those that have
+    // spent more than recovery_after rounds in their status back to healthy, returns them sorted
+    // vaccinated agents keep their immunity
+    pub fn recover_agents(&mut self, recovery_after: usize) -> Vec<u32> {
+        let mut recovered = Vec::new();
+
+        for agent in self.agents.values_mut() {
+            if agent.is_healthy() || agent.vaccinated {
+                continue;
+            }
+
+            agent.rounds_in_state += 1;
+            if agent.rounds_in_state > recovery_after {
+                agent.infection_status = InfectionStatus::Healthy;
+                agent.infected_by = None;
+                agent.rounds_in_state = 0;
+                recovered.push(agent.id);
+            }
+        }
+
+        recovered.sort_unstable();
+        recovered
     }
 
     // apply debate outcome
@@ -109,20 +273,22 @@ impl Registry {
         proposer_id: u32,
         opposer_id: u32,
         outcome: DebateOutcome,
-    ) -> anyhow::Result<()> {
+    ) -> Result<(), RegistryError> {
         let opposer = self
             .agents
             .get_mut(&opposer_id)
-            .with_context(|| format!("opposer agent with id {} not found", opposer_id))?;
+            .ok_or(RegistryError::AgentNotFound(opposer_id))?;
 
         // Apply outcome
         match outcome {
             DebateOutcome::ProposerWon => {
                 opposer.infected_by = Some(proposer_id);
                 opposer.infection_status = InfectionStatus::Infected;
+                opposer.rounds_in_state = 0;
             }
             DebateOutcome::OpposerWon => {
                 opposer.infection_status = InfectionStatus::Immune;
+                opposer.rounds_in_state = 0;
             }
             DebateOutcome::Ongoing => {}
         }
@@ -130,57 +296,210 @@ impl Registry {
         Ok(())
     }
 
+    // reverse apply_debate_outcome, the opposer goes back to healthy. the registry keeps no
+    // history, so this assumes the opposer was healthy before the debate, as can_debate
+    // requires. an opposer that was already immune (a reinfect debate) can't be told apart
+    // and ends up healthy too, callers using can_debate_reinfect have to restore it themselves.
+    // errors if the opposer isn't in the state the outcome leaves, e.g. undoing twice
+    pub fn undo_outcome(
+        &mut self,
+        proposer_id: u32,
+        opposer_id: u32,
+        outcome: DebateOutcome,
+    ) -> Result<(), RegistryError> {
+        let opposer = self
+            .agents
+            .get_mut(&opposer_id)
+            .ok_or(RegistryError::AgentNotFound(opposer_id))?;
+
+        let applied = match outcome {
+            DebateOutcome::ProposerWon => {
+                opposer.is_infected() && opposer.infected_by == Some(proposer_id)
+            }
+            // vaccinated agents were immune before any debate
+            DebateOutcome::OpposerWon => opposer.is_immune() && !opposer.vaccinated,
+            DebateOutcome::Ongoing => return Ok(()),
+        };
+        if !applied {
+            return Err(RegistryError::CannotUndo(opposer_id));
+        }
+
+        opposer.infection_status = InfectionStatus::Healthy;
+        opposer.infected_by = None;
+        opposer.rounds_in_state = 0;
+        Ok(())
+    }
+
+    // apply every opposer's verdict of a group debate, nothing is applied if any agent is missing
+    pub fn apply_group_outcome(&mut self, debate: &GroupDebate) -> Result<(), RegistryError> {
+        let missing: Vec<u32> = debate
+            .outcomes
+            .iter()
+            .map(|&(id, _)| id)
+            .filter(|id| !self.agents.contains_key(id))
+            .collect();
+        if !missing.is_empty() {
+            return Err(RegistryError::AgentsNotFound(missing));
+        }
+
+        for &(opposer_id, outcome) in &debate.outcomes {
+            self.apply_debate_outcome(debate.proposer_id, opposer_id, outcome)?;
+        }
+        Ok(())
+    }
+
+    // copy a finished debate into both participants' histories
+    pub fn record_debate(&mut self, debate: &Debate) -> Result<(), RegistryError> {
+        for id in [debate.proposer_id, debate.opposer_id] {
+            self.agents
+                .get_mut(&id)
+                .ok_or(RegistryError::AgentNotFound(id))?
+                .add_debate(debate.clone());
+        }
+
+        Ok(())
+    }
+
     // validate debate agents
-    pub fn can_debate(&self, proposer_id: u32, opposer_id: u32) -> anyhow::Result<()> {
+    pub fn can_debate(&self, proposer_id: u32, opposer_id: u32) -> Result<(), RegistryError> {
+        self.check_debate(proposer_id, opposer_id, false)
+    }
+
+    // reinfection rule, like can_debate but immune agents that won a debate can be
+    // challenged again. vaccinated agents stay off limits
+    pub fn can_debate_reinfect(
+        &self,
+        proposer_id: u32,
+        opposer_id: u32,
+    ) -> Result<(), RegistryError> {
+        self.check_debate(proposer_id, opposer_id, true)
+    }
+
+    fn check_debate(
+        &self,
+        proposer_id: u32,
+        opposer_id: u32,
+        reinfect_immune: bool,
+    ) -> Result<(), RegistryError> {
         // Check both agents exist
         let proposer = self
             .agents
             .get(&proposer_id)
-            .with_context(|| format!("proposer {} not found", proposer_id))?;
+            .ok_or(RegistryError::AgentNotFound(proposer_id))?;
         let opposer = self
             .agents
             .get(&opposer_id)
-            .with_context(|| format!("opposer {} not found", opposer_id))?;
+            .ok_or(RegistryError::AgentNotFound(opposer_id))?;
 
         // Check proposer is infected
         if !proposer.is_infected() {
-            anyhow::bail!(format!("proposer {} is not infected", proposer_id))
+            return Err(RegistryError::NotInfected(proposer_id));
         }
 
         // Check opposer is healthy
-        if !opposer.is_healthy() {
-            anyhow::bail!(format!("opposer {} is not healthy", opposer_id))
+        if !is_target(opposer, reinfect_immune) {
+            return Err(RegistryError::NotHealthy(opposer_id));
         }
 
         // Check topology exist
-        let topology = match &self.topology {
-            Some(t) => t,
-            None => anyhow::bail!("Topology does not exist"),
-        };
+        let topology = self.topology.as_ref().ok_or(RegistryError::NoTopology)?;
 
         // Check connection exists
         if !topology.are_connected(proposer_id, opposer_id) {
-            anyhow::bail!(format!(
-                "Agents {} and {} are not connected",
-                proposer_id, opposer_id
-            ))
+            return Err(RegistryError::NotConnected(proposer_id, opposer_id));
         }
 
         Ok(())
     }
 
-    // get healthy agents connected to a given id
+    // get healthy agents connected to a given id, heaviest edge first then by id so
+    // scheduling is reproducible
     pub fn get_potential_targets(&self, infector_id: u32) -> Vec<u32> {
+        self.targets(infector_id, false)
+    }
+
+    // get_potential_targets plus immune agents that can be re-challenged, see can_debate_reinfect
+    pub fn get_reinfection_targets(&self, infector_id: u32) -> Vec<u32> {
+        self.targets(infector_id, true)
+    }
+
+    fn targets(&self, infector_id: u32, reinfect_immune: bool) -> Vec<u32> {
         let topology = match &self.topology {
             Some(t) => t,
             None => return Vec::new(),
         };
 
-        topology
+        let mut targets: Vec<u32> = topology
             .get_neighbors(infector_id)
             .into_iter()
-            .filter(|id| self.agents.get(id).map(|a| a.is_healthy()).unwrap_or(false))
-            .collect()
+            .filter(|id| {
+                self.agents
+                    .get(id)
+                    .map(|a| is_target(a, reinfect_immune))
+                    .unwrap_or(false)
+            })
+            .collect();
+        let weight = |id: u32| topology.edge_weight(infector_id, id).unwrap_or(1.0);
+        targets.sort_unstable_by(|&a, &b| weight(b).total_cmp(&weight(a)).then(a.cmp(&b)));
+        targets
+    }
+
+    // infector id -> agents it directly infected, sorted
+    pub fn infection_tree(&self) -> HashMap<u32, Vec<u32>> {
+        let mut tree: HashMap<u32, Vec<u32>> = HashMap::new();
+        for agent in self.agents.values() {
+            if let Some(infector) = agent.infected_by {
+                tree.entry(infector).or_default().push(agent.id);
+            }
+        }
+        for infected in tree.values_mut() {
+            infected.sort_unstable();
+        }
+        tree
+    }
+
+    // agents directly infected by id
+    pub fn direct_infections(&self, id: u32) -> usize {
+        self.agents
+            .values()
+            .filter(|agent| agent.infected_by == Some(id))
+            .count()
+    }
+
+    // size of the infection subtree below id, id itself not counted
+    pub fn total_descendants(&self, id: u32) -> usize {
+        let tree = self.infection_tree();
+        let mut seen = HashSet::from([id]);
+        let mut stack = vec![id];
+
+        while let Some(infector) = stack.pop() {
+            // seen guards against infected_by cycles
+            for &infected in tree.get(&infector).into_iter().flatten() {
+                if seen.insert(infected) {
+                    stack.push(infected);
+                }
+            }
+        }
+
+        seen.len() - 1
+    }
+
+    // walk infected_by from an agent up to its patient zero, agent first
+    pub fn infection_chain(&self, id: u32) -> Vec<u32> {
+        let mut chain = Vec::new();
+        let mut seen = HashSet::new();
+        let mut current = Some(id);
+
+        while let Some(agent_id) = current {
+            // stop on unknown agents or a cycle
+            if !seen.insert(agent_id) || !self.agents.contains_key(&agent_id) {
+                break;
+            }
+            chain.push(agent_id);
+            current = self.agents[&agent_id].infected_by;
+        }
+
+        chain
     }
 
     // registry stats, return struct has other methods
@@ -198,6 +517,50 @@ impl Registry {
         }
     }
 
+    // checkpoint agents, counter and topology
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        serde_json::to_string(self).context("failed to serialize registry")
+    }
+
+    // restore a checkpoint from to_json
+    pub fn from_json(s: &str) -> anyhow::Result<Registry> {
+        serde_json::from_str(s).context("failed to deserialize registry")
+    }
+
+    // stats broken down per model, total_connections counts edges touching that models agents
+    pub fn model_statistics(&self) -> HashMap<String, RegistryStatistics> {
+        let mut stats: HashMap<String, RegistryStatistics> = HashMap::new();
+
+        for agent in self.agents.values() {
+            let entry = stats.entry(agent.model.clone()).or_default();
+
+            entry.total_agents += 1;
+            match agent.infection_status {
+                InfectionStatus::Infected => entry.infected_agents += 1,
+                InfectionStatus::Healthy => entry.healthy_agents += 1,
+                InfectionStatus::Immune => entry.immune_agents += 1,
+            }
+        }
+
+        if let Some(topology) = &self.topology {
+            for (agent_a, agent_b) in topology.get_all_connections() {
+                let model_a = self.agents.get(&agent_a).map(|a| &a.model);
+                let model_b = self.agents.get(&agent_b).map(|a| &a.model);
+
+                if let Some(model) = model_a {
+                    stats.get_mut(model).unwrap().total_connections += 1;
+                }
+                if let Some(model) = model_b
+                    && model_b != model_a
+                {
+                    stats.get_mut(model).unwrap().total_connections += 1;
+                }
+            }
+        }
+
+        stats
+    }
+
     pub fn get_graph_data(&self) -> (Vec<u32>, Vec<(u32, u32)>) {
         let agent_ids = self.get_all_agent_ids();
 
@@ -211,14 +574,84 @@ impl Registry {
     }
 }
 
+#[cfg(feature = "petgraph")]
+impl Registry {
+    // agents as node payloads in id order, agents outside the topology have no edges
+    pub fn to_petgraph(&self) -> StableGraph<Agent, ()> {
+        let agents = self.get_all_agents();
+        let connections = match &self.topology {
+            Some(topology) => topology.get_all_connections(),
+            None => Vec::new(),
+        };
+
+        crate::topology::petgraph_from(
+            agents.into_iter().map(|agent| (agent.id, agent.clone())),
+            connections,
+        )
+    }
+}
+
 impl Default for Registry {
     fn default() -> Self {
         Self::new()
     }
 }
 
+// fluent setup, agents get ids in the order their models are added
+#[derive(Debug, Clone, Default)]
+pub struct RegistryBuilder {
+    models: Vec<String>,
+    topology: Option<Topology>,
+    patient_zeros: Vec<u32>,
+}
+
+impl RegistryBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // one agent per model, can be called more than once
+    pub fn agents(mut self, models: &[&str]) -> Self {
+        self.models
+            .extend(models.iter().map(|model| model.to_string()));
+        self
+    }
+
+    pub fn topology(mut self, topology: Topology) -> Self {
+        self.topology = Some(topology);
+        self
+    }
+
+    // can be called more than once for several patient zeros
+    pub fn patient_zero(mut self, agent_id: u32) -> Self {
+        self.patient_zeros.push(agent_id);
+        self
+    }
+
+    // fails if the topology or a patient zero references an agent that wasnt added
+    pub fn build(self) -> Result<Registry, RegistryError> {
+        let mut registry = Registry::new();
+        for model in self.models {
+            registry.create_agent(model);
+        }
+
+        registry.topology = self.topology;
+        registry.validate_topology()?;
+
+        for agent_id in self.patient_zeros {
+            registry.infect_patient_init(agent_id)?;
+        }
+        Ok(registry)
+    }
+}
+
+// healthy agents can always be debated, immune ones only under the reinfection rule
+fn is_target(agent: &Agent, reinfect_immune: bool) -> bool {
+    agent.is_healthy() || (reinfect_immune && agent.is_immune() && !agent.vaccinated)
+}
+
 // registry stats and methods
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct RegistryStatistics {
     pub total_agents: usize,
     pub infected_agents: usize,
@@ -227,9 +660,36 @@ pub struct RegistryStatistics {
     pub total_connections: usize,
 }
 
+impl RegistryStatistics {
+    pub fn infection_rate(&self) -> f64 {
+        if self.total_agents == 0 {
+            0.0
+        } else {
+            self.infected_agents as f64 / self.total_agents as f64
+        }
+    }
+
+    pub fn immunity_rate(&self) -> f64 {
+        if self.total_agents == 0 {
+            0.0
+        } else {
+            self.immune_agents as f64 / self.total_agents as f64
+        }
+    }
+
+    pub fn healthy_rate(&self) -> f64 {
+        if self.total_agents == 0 {
+            0.0
+        } else {
+            self.healthy_agents as f64 / self.total_agents as f64
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::topology::TopologyBuilder;
 
     #[test]
     fn test_registry_creation() {
@@ -238,6 +698,96 @@ mod tests {
         assert_eq!(registry.infected_count(), 0);
     }
 
+    #[test]
+    fn test_id_lists_are_sorted() {
+        let mut registry = Registry::default();
+        for _ in 0..50 {
+            registry.create_agent("model".to_string());
+        }
+        registry.infect_patients_init(&[40, 3, 17, 29]).unwrap();
+        let is_sorted = |ids: &[u32]| ids.windows(2).all(|pair| pair[0] < pair[1]);
+
+        assert_eq!(registry.get_all_agent_ids(), (0..50).collect::<Vec<u32>>());
+        assert_eq!(registry.get_infected_agent_ids(), vec![3, 17, 29, 40]);
+        assert!(is_sorted(&registry.get_healthy_agent_ids()));
+        assert!(is_sorted(&registry.get_agent_ids_by_model("model")));
+        let agent_ids: Vec<u32> = registry.get_all_agents().iter().map(|a| a.id).collect();
+        assert!(is_sorted(&agent_ids));
+    }
+
+    #[test]
+    fn test_registry_builder() {
+        let registry = RegistryBuilder::new()
+            .agents(&["gpt-4", "weak", "weak"])
+            .topology(TopologyBuilder::star(0, &[1, 2]))
+            .patient_zero(0)
+            .build()
+            .unwrap();
+
+        assert_eq!(registry.agent_count(), 3);
+        assert_eq!(registry.get_agent(0).unwrap().model, "gpt-4");
+        assert_eq!(registry.get_infected_agent_ids(), vec![0]);
+        assert_eq!(registry.get_potential_targets(0), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_registry_builder_rejects_unknown_ids() {
+        let unknown_patient = RegistryBuilder::new()
+            .agents(&["model", "model"])
+            .patient_zero(5)
+            .build();
+        assert_eq!(
+            unknown_patient.unwrap_err(),
+            RegistryError::AgentNotFound(5)
+        );
+
+        let unknown_topology = RegistryBuilder::new()
+            .agents(&["model", "model"])
+            .topology(TopologyBuilder::ring(&[0, 1, 9, 7]))
+            .build();
+        assert_eq!(
+            unknown_topology.unwrap_err(),
+            RegistryError::UnknownTopologyAgents(vec![7, 9])
+        );
+    }
+
+    #[test]
+    fn test_split_components_and_absorb() {
+        let mut registry = Registry::default();
+        for _ in 0..6 {
+            registry.create_agent("model".to_string());
+        }
+        let mut topology = TopologyBuilder::ring(&[0, 1, 2]);
+        topology.add_connection(3, 4);
+        registry.topology = Some(topology);
+
+        let mut parts = registry.split_components();
+
+        let ids: Vec<Vec<u32>> = parts.iter().map(|part| part.get_all_agent_ids()).collect();
+        assert_eq!(ids, vec![vec![0, 1, 2], vec![3, 4], vec![5]]);
+        assert_eq!(parts[0].topology.as_ref().unwrap().connection_count(), 3);
+        assert_eq!(parts[1].topology.as_ref().unwrap().connection_count(), 1);
+
+        parts[1].infect_patient_init(4).unwrap();
+        registry.absorb(parts.remove(1));
+        assert_eq!(registry.get_infected_agent_ids(), vec![4]);
+        assert_eq!(registry.agent_count(), 6);
+    }
+
+    #[test]
+    fn test_validate_topology() {
+        let mut registry = Registry::default();
+        registry.create_agent("model".to_string());
+        registry.create_agent("model".to_string());
+        assert_eq!(registry.validate_topology(), Ok(()));
+
+        registry.topology = Some(TopologyBuilder::star(0, &[1, 99]));
+        assert_eq!(
+            registry.validate_topology(),
+            Err(RegistryError::UnknownTopologyAgents(vec![99]))
+        );
+    }
+
     #[test]
     fn test_create_agent() {
         let mut registry = Registry::default();
@@ -252,6 +802,34 @@ mod tests {
         assert!(registry.get_agent(agent2).is_some());
     }
 
+    #[test]
+    fn test_remove_agent() {
+        let mut registry = Registry::default();
+        let agent_a = registry.create_agent("model".to_string());
+        let agent_b = registry.create_agent("model".to_string());
+        let agent_c = registry.create_agent("model".to_string());
+
+        // a is the hub
+        let mut topology = Topology::new();
+        topology.add_connection(agent_a, agent_b);
+        topology.add_connection(agent_a, agent_c);
+        topology.add_connection(agent_b, agent_c);
+        registry.topology = Some(topology);
+
+        let removed = registry.remove_agent(agent_a).unwrap();
+        assert_eq!(removed.id, agent_a);
+        assert!(registry.get_agent(agent_a).is_none());
+        assert_eq!(registry.agent_count(), 2);
+
+        let topology = registry.topology.as_ref().unwrap();
+        assert!(!topology.get_neighbors(agent_b).contains(&agent_a));
+        assert!(!topology.get_neighbors(agent_c).contains(&agent_a));
+        assert_eq!(topology.connection_count(), 1);
+
+        // already gone
+        assert!(registry.remove_agent(agent_a).is_none());
+    }
+
     #[test]
     fn test_patient_init() {
         let mut registry = Registry::default();
@@ -263,6 +841,112 @@ mod tests {
         assert_eq!(registry.healthy_count(), 0);
     }
 
+    #[test]
+    fn test_patients_init() {
+        let mut registry = Registry::default();
+        let agent_a = registry.create_agent("model".to_string());
+        let agent_b = registry.create_agent("model".to_string());
+        let _agent_c = registry.create_agent("model".to_string());
+
+        registry.infect_patients_init(&[agent_a, agent_b]).unwrap();
+
+        assert!(registry.get_agent(agent_a).unwrap().is_infected());
+        assert!(registry.get_agent(agent_b).unwrap().is_infected());
+        assert_eq!(registry.infected_count(), 2);
+    }
+
+    #[test]
+    fn test_patients_init_missing_is_atomic() {
+        let mut registry = Registry::default();
+        let agent_a = registry.create_agent("model".to_string());
+
+        let err = registry.infect_patients_init(&[agent_a, 7, 9]).unwrap_err();
+
+        assert_eq!(err, RegistryError::AgentsNotFound(vec![7, 9]));
+        assert!(err.to_string().contains("[7, 9]"));
+        assert_eq!(registry.infected_count(), 0);
+    }
+
+    #[test]
+    fn test_reset_infections() {
+        let mut registry = Registry::default();
+        let agent_a = registry.create_agent("model".to_string());
+        let agent_b = registry.create_agent("model".to_string());
+        let agent_c = registry.create_agent("other".to_string());
+
+        let mut topology = Topology::new();
+        topology.add_connection(agent_a, agent_b);
+        topology.add_connection(agent_a, agent_c);
+        registry.topology = Some(topology);
+
+        registry.infect_patient_init(agent_a).unwrap();
+        registry
+            .apply_debate_outcome(agent_a, agent_b, DebateOutcome::ProposerWon)
+            .unwrap();
+        registry
+            .apply_debate_outcome(agent_a, agent_c, DebateOutcome::OpposerWon)
+            .unwrap();
+
+        registry.reset_infections();
+
+        assert_eq!(registry.infected_count(), 0);
+        assert_eq!(registry.healthy_count(), registry.agent_count());
+        assert_eq!(registry.get_agent(agent_b).unwrap().infected_by, None);
+        assert_eq!(registry.get_agent(agent_c).unwrap().model, "other");
+        assert_eq!(registry.topology.as_ref().unwrap().connection_count(), 2);
+    }
+
+    #[test]
+    fn test_recover_agents() {
+        let mut registry = Registry::default();
+        let agent_a = registry.create_agent("model".to_string());
+        let agent_b = registry.create_agent("model".to_string());
+        let agent_c = registry.create_agent("model".to_string());
+        registry.infect_patient_init(agent_a).unwrap();
+        registry
+            .apply_debate_outcome(agent_a, agent_b, DebateOutcome::ProposerWon)
+            .unwrap();
+
+        assert!(registry.recover_agents(1).is_empty());
+        assert_eq!(registry.get_agent(agent_b).unwrap().rounds_in_state, 1);
+        // healthy agents dont age
+        assert_eq!(registry.get_agent(agent_c).unwrap().rounds_in_state, 0);
+
+        assert_eq!(registry.recover_agents(1), vec![agent_a, agent_b]);
+        let agent = registry.get_agent(agent_b).unwrap();
+        assert!(agent.is_healthy());
+        assert_eq!(agent.infected_by, None);
+        assert_eq!(agent.rounds_in_state, 0);
+    }
+
+    #[test]
+    fn test_vaccinate() {
+        let mut registry = Registry::default();
+        for _ in 0..4 {
+            registry.create_agent("model".to_string());
+        }
+        registry.topology = Some(TopologyBuilder::star(0, &[1, 2, 3]));
+
+        assert_eq!(
+            registry.vaccinate(&[0, 9]),
+            Err(RegistryError::AgentsNotFound(vec![9]))
+        );
+        assert!(registry.get_agent(0).unwrap().is_healthy());
+
+        registry.vaccinate(&[0]).unwrap();
+        registry.infect_patient_init(1).unwrap();
+        let center = registry.get_agent(0).unwrap();
+        assert!(center.is_immune() && center.vaccinated);
+
+        // the only route out of a spoke goes through the center
+        assert_eq!(registry.can_debate(1, 0), Err(RegistryError::NotHealthy(0)));
+        assert!(registry.get_potential_targets(1).is_empty());
+
+        // the debate winners recover, the vaccinated center doesnt
+        assert_eq!(registry.recover_agents(0), vec![1]);
+        assert!(registry.get_agent(0).unwrap().is_immune());
+    }
+
     #[test]
     fn test_apply_debate_outcome_infection() {
         let mut registry = Registry::default();
@@ -307,6 +991,31 @@ mod tests {
         assert!(registry.get_agent(agent_b).unwrap().is_immune());
     }
 
+    #[test]
+    fn test_apply_group_outcome() {
+        let mut registry = Registry::default();
+        for _ in 0..3 {
+            registry.create_agent("model".to_string());
+        }
+        registry.infect_patient_init(0).unwrap();
+        let mut debate = GroupDebate::new(0, vec![1, 2], 1);
+        debate.outcomes = vec![
+            (1, DebateOutcome::ProposerWon),
+            (9, DebateOutcome::OpposerWon),
+        ];
+
+        assert_eq!(
+            registry.apply_group_outcome(&debate),
+            Err(RegistryError::AgentsNotFound(vec![9]))
+        );
+        assert!(registry.get_agent(1).unwrap().is_healthy());
+
+        debate.outcomes[1].0 = 2;
+        registry.apply_group_outcome(&debate).unwrap();
+        assert_eq!(registry.get_agent(1).unwrap().infected_by, Some(0));
+        assert!(registry.get_agent(2).unwrap().is_immune());
+    }
+
     #[test]
     fn test_can_debate_validation() {
         let mut registry = Registry::default();
@@ -319,12 +1028,58 @@ mod tests {
         registry.topology = Some(topology);
 
         // Should fail - proposer not infected
-        assert!(registry.can_debate(agent_a, agent_b).is_err());
+        assert_eq!(
+            registry.can_debate(agent_a, agent_b),
+            Err(RegistryError::NotInfected(agent_a))
+        );
+        assert_eq!(
+            registry.can_debate(agent_a, 7),
+            Err(RegistryError::AgentNotFound(7))
+        );
 
         registry.infect_patient_init(agent_a).unwrap();
 
         // Should succeed
         assert!(registry.can_debate(agent_a, agent_b).is_ok());
+
+        // Should fail - opposer already infected
+        registry.infect_patient_init(agent_b).unwrap();
+        assert_eq!(
+            registry.can_debate(agent_a, agent_b),
+            Err(RegistryError::NotHealthy(agent_b))
+        );
+    }
+
+    #[test]
+    fn test_can_debate_reinfect() {
+        let mut registry = Registry::default();
+        for _ in 0..3 {
+            registry.create_agent("model".to_string());
+        }
+        registry.topology = Some(TopologyBuilder::star(0, &[1, 2]));
+        registry.infect_patient_init(0).unwrap();
+        registry
+            .apply_debate_outcome(0, 1, DebateOutcome::OpposerWon)
+            .unwrap();
+        registry.vaccinate(&[2]).unwrap();
+
+        // default rule, immunity is permanent
+        assert_eq!(registry.can_debate(0, 1), Err(RegistryError::NotHealthy(1)));
+        assert!(registry.get_potential_targets(0).is_empty());
+
+        // reinfection rule, only the agent that won its debate is open again
+        assert_eq!(registry.can_debate_reinfect(0, 1), Ok(()));
+        assert_eq!(
+            registry.can_debate_reinfect(0, 2),
+            Err(RegistryError::NotHealthy(2))
+        );
+        assert_eq!(registry.get_reinfection_targets(0), vec![1]);
+
+        // losing the rematch costs the immunity
+        registry
+            .apply_debate_outcome(0, 1, DebateOutcome::ProposerWon)
+            .unwrap();
+        assert!(registry.get_agent(1).unwrap().is_infected());
     }
 
     #[test]
@@ -334,9 +1089,38 @@ mod tests {
         let agent_b = registry.create_agent("model".to_string());
 
         registry.infect_patient_init(agent_a).unwrap();
+        assert_eq!(
+            registry.can_debate(agent_a, agent_b),
+            Err(RegistryError::NoTopology)
+        );
 
         // Should fail - not connected
-        assert!(registry.can_debate(agent_a, agent_b).is_err());
+        registry.topology = Some(Topology::new());
+        assert_eq!(
+            registry.can_debate(agent_a, agent_b),
+            Err(RegistryError::NotConnected(agent_a, agent_b))
+        );
+    }
+
+    #[test]
+    fn test_can_debate_directed() {
+        let mut registry = Registry::default();
+        let agent_a = registry.create_agent("model".to_string());
+        let agent_b = registry.create_agent("model".to_string());
+        registry.topology = Some(TopologyBuilder::directed_from_edges(&[(agent_a, agent_b)]));
+
+        registry.infect_patient_init(agent_a).unwrap();
+        assert_eq!(registry.can_debate(agent_a, agent_b), Ok(()));
+        assert_eq!(registry.get_potential_targets(agent_a), vec![agent_b]);
+
+        // b can't reach back along a -> b
+        registry.reset_infections();
+        registry.infect_patient_init(agent_b).unwrap();
+        assert_eq!(
+            registry.can_debate(agent_b, agent_a),
+            Err(RegistryError::NotConnected(agent_b, agent_a))
+        );
+        assert!(registry.get_potential_targets(agent_b).is_empty());
     }
 
     #[test]
@@ -355,9 +1139,181 @@ mod tests {
         registry.infect_patient_init(agent_a).unwrap();
 
         let targets = registry.get_potential_targets(agent_a);
-        assert_eq!(targets.len(), 2);
-        assert!(targets.contains(&agent_b));
-        assert!(targets.contains(&agent_c));
+        assert_eq!(targets, vec![agent_b, agent_c]);
+
+        // heavier edges are contested first
+        registry
+            .topology
+            .as_mut()
+            .unwrap()
+            .add_weighted_connection(agent_a, agent_c, 3.0);
+        assert_eq!(
+            registry.get_potential_targets(agent_a),
+            vec![agent_c, agent_b]
+        );
+    }
+
+    #[test]
+    fn test_infection_tree_and_chain() {
+        let mut registry = Registry::default();
+        let agent_a = registry.create_agent("model".to_string());
+        let agent_b = registry.create_agent("model".to_string());
+        let agent_c = registry.create_agent("model".to_string());
+        let agent_d = registry.create_agent("model".to_string());
+
+        registry.topology = Some(TopologyBuilder::fully_connected(&[
+            agent_a, agent_b, agent_c, agent_d,
+        ]));
+
+        // a -> b -> c, a -> d
+        registry.infect_patient_init(agent_a).unwrap();
+        registry
+            .apply_debate_outcome(agent_a, agent_b, DebateOutcome::ProposerWon)
+            .unwrap();
+        registry
+            .apply_debate_outcome(agent_b, agent_c, DebateOutcome::ProposerWon)
+            .unwrap();
+        registry
+            .apply_debate_outcome(agent_a, agent_d, DebateOutcome::ProposerWon)
+            .unwrap();
+
+        let tree = registry.infection_tree();
+        assert_eq!(tree[&agent_a], vec![agent_b, agent_d]);
+        assert_eq!(tree[&agent_b], vec![agent_c]);
+        assert!(!tree.contains_key(&agent_c));
+
+        assert_eq!(
+            registry.infection_chain(agent_c),
+            vec![agent_c, agent_b, agent_a]
+        );
+        assert_eq!(registry.infection_chain(agent_a), vec![agent_a]);
+    }
+
+    #[test]
+    fn test_undo_outcome() {
+        let mut registry = Registry::default();
+        let proposer = registry.create_agent("model".to_string());
+        let opposer = registry.create_agent("model".to_string());
+        let vaccinated = registry.create_agent("model".to_string());
+        registry.vaccinate(&[vaccinated]).unwrap();
+        registry.infect_patient_init(proposer).unwrap();
+
+        registry
+            .apply_debate_outcome(proposer, opposer, DebateOutcome::ProposerWon)
+            .unwrap();
+        // wrong proposer
+        assert_eq!(
+            registry.undo_outcome(vaccinated, opposer, DebateOutcome::ProposerWon),
+            Err(RegistryError::CannotUndo(opposer))
+        );
+        registry
+            .undo_outcome(proposer, opposer, DebateOutcome::ProposerWon)
+            .unwrap();
+        let agent = registry.get_agent(opposer).unwrap();
+        assert!(agent.is_healthy());
+        assert_eq!(agent.infected_by, None);
+
+        registry
+            .apply_debate_outcome(proposer, opposer, DebateOutcome::OpposerWon)
+            .unwrap();
+        registry
+            .undo_outcome(proposer, opposer, DebateOutcome::OpposerWon)
+            .unwrap();
+        assert!(registry.get_agent(opposer).unwrap().is_healthy());
+
+        // nothing left to undo
+        assert_eq!(
+            registry.undo_outcome(proposer, opposer, DebateOutcome::OpposerWon),
+            Err(RegistryError::CannotUndo(opposer))
+        );
+        assert_eq!(
+            registry.undo_outcome(proposer, vaccinated, DebateOutcome::OpposerWon),
+            Err(RegistryError::CannotUndo(vaccinated))
+        );
+        assert!(registry.get_agent(vaccinated).unwrap().is_immune());
+    }
+
+    #[test]
+    fn test_iter_by_status() {
+        let mut registry = Registry::default();
+        for _ in 0..5 {
+            registry.create_agent("model".to_string());
+        }
+        registry.infect_patients_init(&[1, 3]).unwrap();
+        registry.vaccinate(&[4]).unwrap();
+
+        let mut infected: Vec<u32> = registry
+            .iter_by_status(InfectionStatus::Infected)
+            .map(|(id, agent)| {
+                assert!(agent.is_infected());
+                id
+            })
+            .collect();
+        infected.sort_unstable();
+        assert_eq!(infected, registry.get_infected_agent_ids());
+        assert_eq!(infected, vec![1, 3]);
+        assert_eq!(registry.iter_by_status(InfectionStatus::Healthy).count(), 2);
+        assert_eq!(registry.iter_by_status(InfectionStatus::Immune).count(), 1);
+    }
+
+    #[test]
+    fn test_descendant_counts() {
+        let mut registry = Registry::default();
+        let ids: Vec<u32> = (0..4)
+            .map(|_| registry.create_agent("model".to_string()))
+            .collect();
+        registry.topology = Some(TopologyBuilder::ring(&ids));
+
+        // 0 -> 1 -> 2 -> 3
+        registry.infect_patient_init(ids[0]).unwrap();
+        for pair in ids.windows(2) {
+            registry
+                .apply_debate_outcome(pair[0], pair[1], DebateOutcome::ProposerWon)
+                .unwrap();
+        }
+
+        assert_eq!(registry.direct_infections(ids[0]), 1);
+        assert_eq!(registry.total_descendants(ids[0]), 3);
+        assert_eq!(registry.total_descendants(ids[2]), 1);
+        assert_eq!(registry.direct_infections(ids[3]), 0);
+        assert_eq!(registry.total_descendants(ids[3]), 0);
+    }
+
+    #[test]
+    fn test_model_statistics() {
+        let mut registry = Registry::default();
+        let strong = registry.create_agent("strong".to_string());
+        let weak_a = registry.create_agent("weak".to_string());
+        let weak_b = registry.create_agent("weak".to_string());
+
+        registry.topology = Some(TopologyBuilder::star(strong, &[weak_a, weak_b]));
+
+        // strong model beats one weak agent, the other fights it off
+        registry.infect_patient_init(strong).unwrap();
+        registry
+            .apply_debate_outcome(strong, weak_a, DebateOutcome::OpposerWon)
+            .unwrap();
+        registry
+            .apply_debate_outcome(strong, weak_b, DebateOutcome::OpposerWon)
+            .unwrap();
+
+        assert_eq!(
+            registry.get_agent_ids_by_model("weak"),
+            vec![weak_a, weak_b]
+        );
+        assert!(registry.get_agent_ids_by_model("missing").is_empty());
+
+        let stats = registry.model_statistics();
+        let weak = &stats["weak"];
+        assert_eq!(weak.total_agents, 2);
+        assert_eq!(weak.immune_agents, 2);
+        assert_eq!(weak.infected_agents, 0);
+        assert_eq!(weak.total_connections, 2);
+
+        let strong = &stats["strong"];
+        assert_eq!(strong.total_agents, 1);
+        assert_eq!(strong.infected_agents, 1);
+        assert_eq!(strong.total_connections, 2);
     }
 
     #[test]
@@ -375,4 +1331,88 @@ mod tests {
         assert_eq!(stats.healthy_agents, 2);
         assert_eq!(stats.immune_agents, 0);
     }
+
+    #[test]
+    fn test_json_round_trip() {
+        let mut registry = Registry::default();
+        let agent_a = registry.create_agent("model".to_string());
+        let agent_b = registry.create_agent("model".to_string());
+        let agent_c = registry.create_agent("other".to_string());
+
+        let mut topology = Topology::new();
+        topology.add_connection(agent_a, agent_b);
+        topology.add_connection(agent_b, agent_c);
+        registry.topology = Some(topology);
+
+        registry.infect_patient_init(agent_a).unwrap();
+        registry
+            .apply_debate_outcome(agent_a, agent_b, DebateOutcome::ProposerWon)
+            .unwrap();
+        registry
+            .get_agent_mut(agent_b)
+            .unwrap()
+            .add_debate(Debate::new(agent_a, agent_b, 2));
+
+        let json = registry.to_json().unwrap();
+        let mut restored = Registry::from_json(&json).unwrap();
+
+        assert_eq!(restored.get_statistics(), registry.get_statistics());
+        assert_eq!(
+            restored.get_agent(agent_b).unwrap().infected_by,
+            Some(agent_a)
+        );
+        assert_eq!(restored.get_agent(agent_c).unwrap().model, "other");
+        assert_eq!(
+            restored.get_agent(agent_b).unwrap().debate_history,
+            registry.get_agent(agent_b).unwrap().debate_history
+        );
+
+        // counter survives so new ids dont collide
+        assert_eq!(restored.create_agent("model".to_string()), 3);
+    }
+
+    #[cfg(feature = "petgraph")]
+    #[test]
+    fn test_to_petgraph() {
+        let mut registry = Registry::default();
+        for _ in 0..4 {
+            registry.create_agent("model".to_string());
+        }
+        // agent 3 is isolated
+        registry.topology = Some(TopologyBuilder::fully_connected(&[0, 1, 2]));
+
+        let graph = registry.to_petgraph();
+
+        assert_eq!(graph.node_count(), 4);
+        assert_eq!(graph.edge_count(), 3);
+        assert_eq!(
+            graph
+                .node_weights()
+                .map(|agent| agent.id)
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn test_statistics_rates() {
+        let mut registry = Registry::default();
+        assert_eq!(registry.get_statistics().healthy_rate(), 0.0);
+
+        for _ in 0..4 {
+            registry.create_agent("model".to_string());
+        }
+        registry.topology = Some(TopologyBuilder::star(0, &[1, 2, 3]));
+        registry.infect_patient_init(0).unwrap();
+        registry
+            .apply_debate_outcome(0, 1, DebateOutcome::OpposerWon)
+            .unwrap();
+
+        let stats = registry.get_statistics();
+        assert_eq!(stats.infection_rate(), 0.25);
+        assert_eq!(stats.immunity_rate(), 0.25);
+        assert_eq!(stats.healthy_rate(), 0.5);
+        let total = stats.infection_rate() + stats.immunity_rate() + stats.healthy_rate();
+        assert!((total - 1.0).abs() < 1e-9);
+    }
 }
@@ -1,17 +1,101 @@
-use rand::Rng;
-use std::collections::{HashMap, HashSet};
+use crate::error::TopologyError;
+#[cfg(feature = "petgraph")]
+use petgraph::stable_graph::StableGraph;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 
 // network topology of agents
-#[derive(Debug, Clone)]
+// serialized as an edge list since the adjacency map stores every edge twice
+//
+// undirected by default, add_connection(a, b) lets either agent debate the other.
+// a directed topology (new_directed) only stores a -> b: a can debate b but not the
+// reverse, neighbors and degrees are outgoing and every (a, b) pair is its own connection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(into = "TopologyData", from = "TopologyData")]
 pub struct Topology {
     connections: HashMap<u32, HashSet<u32>>,
+    // keyed by (smaller id, larger id) or (from, to) when directed, connections without an
+    // entry weigh 1.0
+    weights: HashMap<(u32, u32), f64>,
+    directed: bool,
+}
+
+// on disk form of topology
+#[derive(Serialize, Deserialize)]
+struct TopologyData {
+    connections: Vec<(u32, u32)>,
+    // only edges whose weight isnt 1.0
+    #[serde(default)]
+    weights: Vec<(u32, u32, f64)>,
+    #[serde(default)]
+    directed: bool,
+}
+
+impl From<Topology> for TopologyData {
+    fn from(topology: Topology) -> Self {
+        let mut weights: Vec<(u32, u32, f64)> = topology
+            .weights
+            .iter()
+            .map(|(&(agent_a, agent_b), &weight)| (agent_a, agent_b, weight))
+            .collect();
+        weights.sort_by_key(|&(agent_a, agent_b, _)| (agent_a, agent_b));
+
+        Self {
+            connections: topology.get_all_connections(),
+            weights,
+            directed: topology.directed,
+        }
+    }
+}
+
+impl From<TopologyData> for Topology {
+    fn from(data: TopologyData) -> Self {
+        let mut topology = if data.directed {
+            Topology::new_directed()
+        } else {
+            Topology::new()
+        };
+        for (agent_a, agent_b) in data.connections {
+            topology.add_connection(agent_a, agent_b);
+        }
+        for (agent_a, agent_b, weight) in data.weights {
+            topology.add_weighted_connection(agent_a, agent_b, weight);
+        }
+        topology
+    }
 }
 
 impl Topology {
     pub fn new() -> Self {
         Self {
             connections: HashMap::new(),
+            weights: HashMap::new(),
+            directed: false,
+        }
+    }
+
+    // one way connections, see the note on Topology
+    pub fn new_directed() -> Self {
+        Self {
+            directed: true,
+            ..Self::new()
+        }
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    // build from edge list, self loops and duplicates are dropped like add_connection
+    pub fn from_edges(edges: &[(u32, u32)]) -> Self {
+        let mut topology = Self::new();
+        for &(agent_a, agent_b) in edges {
+            topology.add_connection(agent_a, agent_b);
         }
+        topology
     }
 
     pub fn add_connection(&mut self, agent_a: u32, agent_b: u32) {
@@ -26,17 +110,83 @@ impl Topology {
         }
 
         self.connections.entry(agent_a).or_default().insert(agent_b);
-        self.connections.entry(agent_b).or_default().insert(agent_a);
+        // directed targets are still listed as agents, just without the reverse edge
+        let reverse = self.connections.entry(agent_b).or_default();
+        if !self.directed {
+            reverse.insert(agent_a);
+        }
     }
 
-    // remove connection
+    // add every agent and connection of other through add_connection, so duplicates and self
+    // loops stay out. other's weights win where both set one, and this topology keeps its own
+    // direction mode
+    pub fn merge(&mut self, other: &Topology) {
+        for &agent_id in other.connections.keys() {
+            self.connections.entry(agent_id).or_default();
+        }
+        for (agent_a, agent_b) in other.get_all_connections() {
+            match other.weights.get(&other.edge_key(agent_a, agent_b)) {
+                Some(&weight) => self.add_weighted_connection(agent_a, agent_b, weight),
+                None => self.add_connection(agent_a, agent_b),
+            }
+        }
+    }
+
+    // merge into a copy, see merge
+    pub fn union(&self, other: &Topology) -> Topology {
+        let mut union = self.clone();
+        union.merge(other);
+        union
+    }
+
+    // connect (or reweight an existing connection), heavier edges are debated first
+    pub fn add_weighted_connection(&mut self, agent_a: u32, agent_b: u32, weight: f64) {
+        if agent_a == agent_b {
+            return;
+        }
+
+        self.add_connection(agent_a, agent_b);
+        if weight == 1.0 {
+            self.weights.remove(&self.edge_key(agent_a, agent_b));
+        } else {
+            self.weights.insert(self.edge_key(agent_a, agent_b), weight);
+        }
+    }
+
+    // same in both directions, none if the agents arent connected
+    pub fn edge_weight(&self, agent_a: u32, agent_b: u32) -> Option<f64> {
+        if !self.are_connected(agent_a, agent_b) {
+            return None;
+        }
+        Some(
+            self.weights
+                .get(&self.edge_key(agent_a, agent_b))
+                .copied()
+                .unwrap_or(1.0),
+        )
+    }
+
+    // remove connection, only a -> b when directed
     pub fn remove_connection(&mut self, agent_a: u32, agent_b: u32) {
         if let Some(neighbors) = self.connections.get_mut(&agent_a) {
             neighbors.remove(&agent_b);
         }
-        if let Some(neighbors) = self.connections.get_mut(&agent_b) {
+        if !self.directed
+            && let Some(neighbors) = self.connections.get_mut(&agent_b)
+        {
             neighbors.remove(&agent_a);
         }
+        self.weights.remove(&self.edge_key(agent_a, agent_b));
+    }
+
+    // drop an agent and every connection touching it, in either direction
+    pub fn remove_agent(&mut self, agent_id: u32) {
+        self.connections.remove(&agent_id);
+        for neighbors in self.connections.values_mut() {
+            neighbors.remove(&agent_id);
+        }
+        self.weights
+            .retain(|&(agent_a, agent_b), _| agent_a != agent_id && agent_b != agent_id);
     }
 
     // check if connected
@@ -49,10 +199,14 @@ impl Topology {
 
     // retrieve all neighbors for an agent
     pub fn get_neighbors(&self, agent_id: u32) -> Vec<u32> {
-        self.connections
+        let mut neighbors: Vec<u32> = self
+            .connections
             .get(&agent_id)
             .map(|neighbors| neighbors.iter().copied().collect())
-            .unwrap_or_default()
+            .unwrap_or_default();
+        // sorted so callers see the same order every run
+        neighbors.sort_unstable();
+        neighbors
     }
 
     // get len of degrees for an agent
@@ -63,17 +217,19 @@ impl Topology {
             .unwrap_or(0)
     }
 
-    // retrieve connections
+    // retrieve connections, (from, to) pairs when directed. sorted so the order never depends
+    // on hash iteration, e.g. for diffable output
     pub fn get_all_connections(&self) -> Vec<(u32, u32)> {
         let mut temp_con = Vec::new();
         for (&agent_a, neighbors) in &self.connections {
             for &agent_b in neighbors {
                 // use < to avoid duplication connections
-                if agent_a < agent_b {
+                if self.directed || agent_a < agent_b {
                     temp_con.push((agent_a, agent_b));
                 }
             }
         }
+        temp_con.sort_unstable();
         temp_con
     }
 
@@ -86,6 +242,360 @@ impl Topology {
     pub fn get_all_agent_ids(&self) -> HashSet<u32> {
         self.connections.keys().copied().collect()
     }
+
+    // degree -> number of agents with that degree
+    pub fn degree_distribution(&self) -> HashMap<usize, usize> {
+        let mut distribution = HashMap::new();
+        for neighbors in self.connections.values() {
+            *distribution.entry(neighbors.len()).or_insert(0) += 1;
+        }
+        distribution
+    }
+
+    // best connected agent, lowest id wins a tie
+    pub fn max_degree_node(&self) -> Option<u32> {
+        self.connections
+            .iter()
+            .max_by(|(id_a, a), (id_b, b)| a.len().cmp(&b.len()).then(id_b.cmp(id_a)))
+            .map(|(&id, _)| id)
+    }
+
+    // share of possible connections present, 0.0 with fewer than 2 agents
+    pub fn density(&self) -> f64 {
+        let n = self.connections.len();
+        if n < 2 {
+            return 0.0;
+        }
+        // directed graphs have twice as many possible connections
+        let possible = if self.directed {
+            n * (n - 1)
+        } else {
+            n * (n - 1) / 2
+        };
+        self.connection_count() as f64 / possible as f64
+    }
+
+    // analytic share of agents to immunize before an outbreak stops, 1 - 1/r0 with r0 taken
+    // from the degree distribution as <k^2>/<k> - 1 (the mean excess degree, how many new
+    // agents a freshly infected one can reach). assumes every debate infects and ignores
+    // clustering, so treat it as a rough guide for vaccinate. 0.0 when r0 <= 1
+    pub fn herd_immunity_threshold(&self) -> f64 {
+        if self.connections.is_empty() {
+            return 0.0;
+        }
+        let n = self.connections.len() as f64;
+        let degrees = self
+            .connections
+            .values()
+            .map(|neighbors| neighbors.len() as f64);
+        let mean_degree = degrees.clone().sum::<f64>() / n;
+        let mean_square_degree = degrees.map(|k| k * k).sum::<f64>() / n;
+        if mean_degree == 0.0 {
+            return 0.0;
+        }
+
+        let r0 = mean_square_degree / mean_degree - 1.0;
+        if r0 <= 1.0 { 0.0 } else { 1.0 - 1.0 / r0 }
+    }
+
+    // bfs hop count between two agents, none if unreachable
+    pub fn shortest_path_len(&self, from: u32, to: u32) -> Option<usize> {
+        self.distances_from(from).get(&to).copied()
+    }
+
+    // longest finite shortest path, none for an empty topology
+    pub fn diameter(&self) -> Option<usize> {
+        self.connections
+            .keys()
+            .filter_map(|&agent_id| self.distances_from(agent_id).into_values().max())
+            .max()
+    }
+
+    // fraction of an agents neighbor pairs that are connected themselves
+    // agents with fewer than 2 neighbors have no pairs and return 0.0
+    pub fn clustering_coefficient(&self, agent_id: u32) -> f64 {
+        let neighbors = self.get_neighbors(agent_id);
+        let degree = neighbors.len();
+        if degree < 2 {
+            return 0.0;
+        }
+
+        let mut linked_pairs = 0;
+        for i in 0..degree {
+            for j in (i + 1)..degree {
+                if self.are_connected(neighbors[i], neighbors[j])
+                    || self.are_connected(neighbors[j], neighbors[i])
+                {
+                    linked_pairs += 1;
+                }
+            }
+        }
+
+        let possible_pairs = degree * (degree - 1) / 2;
+        linked_pairs as f64 / possible_pairs as f64
+    }
+
+    // mean clustering over agents with degree >= 2, lower degree agents are excluded
+    pub fn average_clustering(&self) -> f64 {
+        let coefficients: Vec<f64> = self
+            .connections
+            .keys()
+            .filter(|&&agent_id| self.get_degree(agent_id) >= 2)
+            .map(|&agent_id| self.clustering_coefficient(agent_id))
+            .collect();
+
+        if coefficients.is_empty() {
+            0.0
+        } else {
+            coefficients.iter().sum::<f64>() / coefficients.len() as f64
+        }
+    }
+
+    // newman's degree assortativity, the pearson correlation of the degrees at either end of
+    // every edge. positive when hubs link to hubs, negative when they link to leaves (a star is
+    // -1.0). direction is ignored, 0.0 without edges or when every agent has the same degree
+    pub fn degree_assortativity(&self) -> f64 {
+        let undirected = self.undirected();
+        let degree = |id: u32| undirected[&id].len() as f64;
+
+        // every edge counted from both ends, so both ends share one mean and variance
+        let (mut ends, mut sum, mut sum_square, mut sum_product) = (0.0, 0.0, 0.0, 0.0);
+        for (&agent_a, neighbors) in &undirected {
+            let degree_a = degree(agent_a);
+            for &agent_b in neighbors {
+                ends += 1.0;
+                sum += degree_a;
+                sum_square += degree_a * degree_a;
+                sum_product += degree_a * degree(agent_b);
+            }
+        }
+        if ends == 0.0 {
+            return 0.0;
+        }
+
+        let mean = sum / ends;
+        let variance = sum_square / ends - mean * mean;
+        if variance <= f64::EPSILON {
+            return 0.0;
+        }
+        (sum_product / ends - mean * mean) / variance
+    }
+
+    // undirected edges share one weight entry
+    fn edge_key(&self, agent_a: u32, agent_b: u32) -> (u32, u32) {
+        if self.directed {
+            (agent_a, agent_b)
+        } else {
+            (agent_a.min(agent_b), agent_a.max(agent_b))
+        }
+    }
+
+    // groups of agents linked by any path (in either direction when directed), each sorted and
+    // ordered by their lowest id
+    pub fn connected_components(&self) -> Vec<Vec<u32>> {
+        let undirected = self.undirected();
+        let mut ids: Vec<u32> = undirected.keys().copied().collect();
+        ids.sort_unstable();
+
+        let mut seen = HashSet::new();
+        let mut components = Vec::new();
+        for id in ids {
+            if !seen.insert(id) {
+                continue;
+            }
+            let mut component = vec![id];
+            let mut stack = vec![id];
+            while let Some(current) = stack.pop() {
+                for &neighbor in &undirected[&current] {
+                    if seen.insert(neighbor) {
+                        component.push(neighbor);
+                        stack.push(neighbor);
+                    }
+                }
+            }
+            component.sort_unstable();
+            components.push(component);
+        }
+
+        components
+    }
+
+    // agents whose removal splits their component, found with the dfs low-link algorithm over
+    // the undirected adjacency. sorted, these are the first candidates to vaccinate
+    pub fn articulation_points(&self) -> Vec<u32> {
+        let adjacency = self.undirected();
+        let mut ids: Vec<u32> = adjacency.keys().copied().collect();
+        ids.sort_unstable();
+
+        // dfs discovery order, and the earliest one reachable through at most one back edge
+        let mut discovered: HashMap<u32, usize> = HashMap::new();
+        let mut low: HashMap<u32, usize> = HashMap::new();
+        let mut points = HashSet::new();
+        for root in ids {
+            if discovered.contains_key(&root) {
+                continue;
+            }
+            discovered.insert(root, discovered.len());
+            low.insert(root, discovered[&root]);
+            let mut root_children = 0;
+
+            // iterative so long chains cant overflow the stack: (agent, parent, next neighbor)
+            let mut stack = vec![(root, None, 0)];
+            while let Some(frame) = stack.last_mut() {
+                let (agent, parent) = (frame.0, frame.1);
+                if let Some(&neighbor) = adjacency[&agent].get(frame.2) {
+                    frame.2 += 1;
+                    if Some(neighbor) == parent {
+                        continue;
+                    }
+                    if let Some(&order) = discovered.get(&neighbor) {
+                        let agent_low = low.get_mut(&agent).unwrap();
+                        *agent_low = (*agent_low).min(order);
+                    } else {
+                        let order = discovered.len();
+                        discovered.insert(neighbor, order);
+                        low.insert(neighbor, order);
+                        stack.push((neighbor, Some(agent), 0));
+                    }
+                    continue;
+                }
+
+                // every neighbor done, hand the low link up to the parent
+                stack.pop();
+                if let Some(parent) = parent {
+                    let child_low = low[&agent];
+                    let parent_low = low.get_mut(&parent).unwrap();
+                    *parent_low = (*parent_low).min(child_low);
+                    if parent == root {
+                        root_children += 1;
+                    } else if child_low >= discovered[&parent] {
+                        points.insert(parent);
+                    }
+                }
+            }
+            // the root only splits the graph if the dfs left it more than once
+            if root_children > 1 {
+                points.insert(root);
+            }
+        }
+
+        let mut points: Vec<u32> = points.into_iter().collect();
+        points.sort_unstable();
+        points
+    }
+
+    // what is left after repeatedly dropping agents with fewer than k neighbors, direction is
+    // ignored for the degrees but kept on the surviving connections
+    pub fn k_core(&self, k: usize) -> Topology {
+        let survivors = self
+            .core_numbers()
+            .into_iter()
+            .filter(|&(_, core)| core >= k)
+            .map(|(id, _)| id)
+            .collect();
+        self.subgraph(&survivors)
+    }
+
+    // largest k whose k-core still holds the agent, 0 for unknown agents
+    pub fn coreness(&self, agent_id: u32) -> usize {
+        self.core_numbers().get(&agent_id).copied().unwrap_or(0)
+    }
+
+    // coreness of every agent, peeling the lowest degree agent (lowest id on ties) each step
+    fn core_numbers(&self) -> HashMap<u32, usize> {
+        let adjacency = self.undirected();
+        let mut degrees: HashMap<u32, usize> = adjacency
+            .iter()
+            .map(|(&id, neighbors)| (id, neighbors.len()))
+            .collect();
+
+        let mut cores = HashMap::new();
+        let mut k = 0;
+        while let Some((&agent, &degree)) =
+            degrees.iter().min_by_key(|&(&id, &degree)| (degree, id))
+        {
+            k = k.max(degree);
+            cores.insert(agent, k);
+            degrees.remove(&agent);
+            for neighbor in &adjacency[&agent] {
+                if let Some(degree) = degrees.get_mut(neighbor) {
+                    *degree -= 1;
+                }
+            }
+        }
+        cores
+    }
+
+    // neighbors ignoring direction, sorted without duplicates
+    fn undirected(&self) -> HashMap<u32, Vec<u32>> {
+        let mut undirected: HashMap<u32, Vec<u32>> = HashMap::new();
+        for (&agent_a, neighbors) in &self.connections {
+            undirected.entry(agent_a).or_default();
+            for &agent_b in neighbors {
+                undirected.entry(agent_a).or_default().push(agent_b);
+                undirected.entry(agent_b).or_default().push(agent_a);
+            }
+        }
+        for neighbors in undirected.values_mut() {
+            neighbors.sort_unstable();
+            neighbors.dedup();
+        }
+        undirected
+    }
+
+    // only the agents in ids and the connections between them, weights and direction kept.
+    // ids the topology does not know are left out, e.g. pass get_infected_agent_ids for the
+    // infected backbone
+    pub fn subgraph(&self, ids: &HashSet<u32>) -> Topology {
+        let mut topology = Topology {
+            directed: self.directed,
+            ..Topology::new()
+        };
+        for (&agent_a, neighbors) in &self.connections {
+            if !ids.contains(&agent_a) {
+                continue;
+            }
+            let kept = neighbors.iter().copied().filter(|id| ids.contains(id));
+            topology.connections.insert(agent_a, kept.collect());
+        }
+        for (&(agent_a, agent_b), &weight) in &self.weights {
+            if ids.contains(&agent_a) && ids.contains(&agent_b) {
+                topology.weights.insert((agent_a, agent_b), weight);
+            }
+        }
+        topology
+    }
+
+    // bfs distances to every reachable agent, source included at 0
+    fn distances_from(&self, source: u32) -> HashMap<u32, usize> {
+        let mut distances = HashMap::from([(source, 0)]);
+        let mut queue = VecDeque::from([source]);
+
+        while let Some(current) = queue.pop_front() {
+            let next_distance = distances[&current] + 1;
+            for neighbor in self.get_neighbors(current) {
+                if let Entry::Vacant(entry) = distances.entry(neighbor) {
+                    entry.insert(next_distance);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        distances
+    }
+}
+
+// same direction mode, connections and weights. agents without any connection are ignored,
+// the edge list on disk does not keep them either
+impl PartialEq for Topology {
+    fn eq(&self, other: &Self) -> bool {
+        let edges = |topology: &Topology| -> HashSet<(u32, u32)> {
+            topology.get_all_connections().into_iter().collect()
+        };
+        self.directed == other.directed
+            && self.weights == other.weights
+            && edges(self) == edges(other)
+    }
 }
 
 impl Default for Topology {
@@ -94,6 +604,42 @@ impl Default for Topology {
     }
 }
 
+#[cfg(feature = "petgraph")]
+impl Topology {
+    // node weights are agent ids, one edge per connection
+    pub fn to_petgraph(&self) -> StableGraph<u32, ()> {
+        let mut ids: Vec<u32> = self.get_all_agent_ids().into_iter().collect();
+        ids.sort_unstable();
+        petgraph_from(
+            ids.into_iter().map(|id| (id, id)),
+            self.get_all_connections(),
+        )
+    }
+}
+
+// nodes are added in the given order, edges touching unknown ids are dropped
+#[cfg(feature = "petgraph")]
+pub(crate) fn petgraph_from<N>(
+    nodes: impl IntoIterator<Item = (u32, N)>,
+    mut edges: Vec<(u32, u32)>,
+) -> StableGraph<N, ()> {
+    let mut graph = StableGraph::new();
+    let mut node_map = HashMap::new();
+
+    for (id, weight) in nodes {
+        node_map.insert(id, graph.add_node(weight));
+    }
+
+    edges.sort_unstable();
+    for (from_id, to_id) in edges {
+        if let (Some(&from), Some(&to)) = (node_map.get(&from_id), node_map.get(&to_id)) {
+            graph.add_edge(from, to, ());
+        }
+    }
+
+    graph
+}
+
 // preset topology helper
 pub struct TopologyBuilder;
 
@@ -115,6 +661,14 @@ impl TopologyBuilder {
     pub fn ring(agent_ids: &[u32]) -> Topology {
         let mut topology = Topology::new();
 
+        // too few agents to close a loop, two share one edge and one or none stay unconnected
+        if agent_ids.len() < 3 {
+            if let &[agent_a, agent_b] = agent_ids {
+                topology.add_connection(agent_a, agent_b);
+            }
+            return topology;
+        }
+
         for i in 0..agent_ids.len() {
             let next = (i + 1) % agent_ids.len();
             topology.add_connection(agent_ids[i], agent_ids[next]);
@@ -124,6 +678,7 @@ impl TopologyBuilder {
     }
 
     // star network 1 centralised agent
+    // whether the ids exist is up to the registry, see Registry::validate_topology
     pub fn star(center: u32, periphery: &[u32]) -> Topology {
         let mut topology = Topology::new();
 
@@ -136,8 +691,24 @@ impl TopologyBuilder {
 
     // random network with random_bool
     pub fn random(agent_ids: &[u32], connection_probability: f64) -> Topology {
+        Self::random_with_rng(agent_ids, connection_probability, &mut rand::rng())
+    }
+
+    // random network that is reproducible for a given seed
+    pub fn random_seeded(agent_ids: &[u32], connection_probability: f64, seed: u64) -> Topology {
+        Self::random_with_rng(
+            agent_ids,
+            connection_probability,
+            &mut StdRng::seed_from_u64(seed),
+        )
+    }
+
+    fn random_with_rng(
+        agent_ids: &[u32],
+        connection_probability: f64,
+        rng: &mut impl Rng,
+    ) -> Topology {
         let mut topology = Topology::new();
-        let mut rng = rand::rng();
 
         for i in 0..agent_ids.len() {
             for j in (i + 1)..agent_ids.len() {
@@ -149,6 +720,261 @@ impl TopologyBuilder {
 
         topology
     }
+
+    // watts-strogatz small world, ring lattice where each agent links to its k nearest
+    // neighbors (k / 2 each side, odd k rounds down) then each edge is rewired with probability beta
+    pub fn small_world(agent_ids: &[u32], k: usize, beta: f64) -> Topology {
+        Self::try_small_world(agent_ids, k, beta).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_small_world(
+        agent_ids: &[u32],
+        k: usize,
+        beta: f64,
+    ) -> Result<Topology, TopologyError> {
+        Self::small_world_with_rng(agent_ids, k, beta, &mut rand::rng())
+    }
+
+    // small world that is reproducible for a given seed
+    pub fn small_world_seeded(agent_ids: &[u32], k: usize, beta: f64, seed: u64) -> Topology {
+        Self::try_small_world_seeded(agent_ids, k, beta, seed)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_small_world_seeded(
+        agent_ids: &[u32],
+        k: usize,
+        beta: f64,
+        seed: u64,
+    ) -> Result<Topology, TopologyError> {
+        Self::small_world_with_rng(agent_ids, k, beta, &mut StdRng::seed_from_u64(seed))
+    }
+
+    fn small_world_with_rng(
+        agent_ids: &[u32],
+        k: usize,
+        beta: f64,
+        rng: &mut impl Rng,
+    ) -> Result<Topology, TopologyError> {
+        check_agent_ids(agent_ids)?;
+        let n = agent_ids.len();
+        // k = 1 has no neighbor on either side and would build no lattice at all
+        if k < 2 || k >= n {
+            return Err(TopologyError::ParameterOutOfRange(format!(
+                "k ({}) must be at least 2 and less than the number of agents ({})",
+                k, n
+            )));
+        }
+        if !(0.0..=1.0).contains(&beta) {
+            return Err(TopologyError::ParameterOutOfRange(format!(
+                "beta ({}) must be between 0.0 and 1.0",
+                beta
+            )));
+        }
+
+        let mut topology = Topology::new();
+
+        // ring lattice
+        for i in 0..n {
+            for j in 1..=k / 2 {
+                topology.add_connection(agent_ids[i], agent_ids[(i + j) % n]);
+            }
+        }
+
+        // rewire lattice edges, only to agents not already linked so the edge count never drops
+        for i in 0..n {
+            for j in 1..=k / 2 {
+                if !rng.random_bool(beta) {
+                    continue;
+                }
+
+                let agent_a = agent_ids[i];
+                let agent_b = agent_ids[(i + j) % n];
+                if !topology.are_connected(agent_a, agent_b) {
+                    continue;
+                }
+
+                let candidates: Vec<u32> = agent_ids
+                    .iter()
+                    .copied()
+                    .filter(|&c| c != agent_a && !topology.are_connected(agent_a, c))
+                    .collect();
+
+                // agent_a already linked to everyone, keep the lattice edge
+                if candidates.is_empty() {
+                    continue;
+                }
+
+                let agent_c = candidates[rng.random_range(0..candidates.len())];
+                topology.remove_connection(agent_a, agent_b);
+                topology.add_connection(agent_a, agent_c);
+            }
+        }
+
+        Ok(topology)
+    }
+
+    // barabasi-albert scale free, m fully connected seed agents then every other agent
+    // attaches to m existing agents picked proportionally to their degree
+    pub fn scale_free(agent_ids: &[u32], m: usize) -> Topology {
+        Self::try_scale_free(agent_ids, m).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_scale_free(agent_ids: &[u32], m: usize) -> Result<Topology, TopologyError> {
+        Self::scale_free_with_rng(agent_ids, m, &mut rand::rng())
+    }
+
+    // scale free network that is reproducible for a given seed
+    pub fn scale_free_seeded(agent_ids: &[u32], m: usize, seed: u64) -> Topology {
+        Self::try_scale_free_seeded(agent_ids, m, seed).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_scale_free_seeded(
+        agent_ids: &[u32],
+        m: usize,
+        seed: u64,
+    ) -> Result<Topology, TopologyError> {
+        Self::scale_free_with_rng(agent_ids, m, &mut StdRng::seed_from_u64(seed))
+    }
+
+    fn scale_free_with_rng(
+        agent_ids: &[u32],
+        m: usize,
+        rng: &mut impl Rng,
+    ) -> Result<Topology, TopologyError> {
+        check_agent_ids(agent_ids)?;
+        let n = agent_ids.len();
+        if m < 1 || m >= n {
+            return Err(TopologyError::ParameterOutOfRange(format!(
+                "m ({}) must be at least 1 and less than the number of agents ({})",
+                m, n
+            )));
+        }
+        let mut topology = Self::fully_connected(&agent_ids[..m]);
+
+        // each agent appears once per degree so uniform picks are degree weighted
+        let mut degree_pool: Vec<u32> = topology
+            .get_all_connections()
+            .into_iter()
+            .flat_map(|(a, b)| [a, b])
+            .collect();
+
+        for i in m..n {
+            let new_agent = agent_ids[i];
+            // ordered so the degree pool, and with it a seeded run, never depends on hash order
+            let mut targets = BTreeSet::new();
+            // the i agents before this one are all the distinct candidates there are
+            let wanted = m.min(i);
+
+            while targets.len() < wanted {
+                // single seed agent has no edges yet, fall back to uniform
+                let target = if degree_pool.is_empty() {
+                    agent_ids[rng.random_range(0..i)]
+                } else {
+                    degree_pool[rng.random_range(0..degree_pool.len())]
+                };
+                targets.insert(target);
+            }
+
+            for target in targets {
+                topology.add_connection(new_agent, target);
+                degree_pool.push(new_agent);
+                degree_pool.push(target);
+            }
+        }
+
+        Ok(topology)
+    }
+
+    // rows x cols lattice laid out row by row, each agent linked to its orthogonal neighbors
+    pub fn grid(rows: usize, cols: usize, agent_ids: &[u32]) -> Topology {
+        Self::try_grid(rows, cols, agent_ids).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_grid(
+        rows: usize,
+        cols: usize,
+        agent_ids: &[u32],
+    ) -> Result<Topology, TopologyError> {
+        Self::lattice(rows, cols, agent_ids, false)
+    }
+
+    // one way connections, each (a, b) lets a debate b but not the other way round
+    pub fn directed_from_edges(edges: &[(u32, u32)]) -> Topology {
+        let mut topology = Topology::new_directed();
+        for &(agent_a, agent_b) in edges {
+            topology.add_connection(agent_a, agent_b);
+        }
+        topology
+    }
+
+    // grid where opposite edges wrap around
+    pub fn torus(rows: usize, cols: usize, agent_ids: &[u32]) -> Topology {
+        Self::try_torus(rows, cols, agent_ids).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_torus(
+        rows: usize,
+        cols: usize,
+        agent_ids: &[u32],
+    ) -> Result<Topology, TopologyError> {
+        Self::lattice(rows, cols, agent_ids, true)
+    }
+
+    fn lattice(
+        rows: usize,
+        cols: usize,
+        agent_ids: &[u32],
+        wrap: bool,
+    ) -> Result<Topology, TopologyError> {
+        check_agent_ids(agent_ids)?;
+        if agent_ids.len() != rows * cols {
+            return Err(TopologyError::WrongAgentCount(rows * cols, agent_ids.len()));
+        }
+
+        let mut topology = Topology::new();
+        let at = |row: usize, col: usize| agent_ids[row * cols + col];
+
+        for row in 0..rows {
+            for col in 0..cols {
+                // right neighbor
+                if col + 1 < cols {
+                    topology.add_connection(at(row, col), at(row, col + 1));
+                } else if wrap {
+                    topology.add_connection(at(row, col), at(row, 0));
+                }
+
+                // bottom neighbor
+                if row + 1 < rows {
+                    topology.add_connection(at(row, col), at(row + 1, col));
+                } else if wrap {
+                    topology.add_connection(at(row, col), at(0, col));
+                }
+            }
+        }
+
+        Ok(topology)
+    }
+}
+
+// shared input checks of the try_ builders, a repeated id would silently shrink the topology
+// (or never finish picking distinct scale free targets)
+fn check_agent_ids(agent_ids: &[u32]) -> Result<(), TopologyError> {
+    if agent_ids.is_empty() {
+        return Err(TopologyError::EmptyAgentList);
+    }
+    let mut seen = HashSet::new();
+    let mut duplicates: Vec<u32> = agent_ids
+        .iter()
+        .copied()
+        .filter(|&id| !seen.insert(id))
+        .collect();
+    if duplicates.is_empty() {
+        return Ok(());
+    }
+    duplicates.sort_unstable();
+    duplicates.dedup();
+    Err(TopologyError::DuplicateAgentIds(duplicates))
 }
 
 #[cfg(test)]
@@ -185,6 +1011,135 @@ mod tests {
         assert!(neighbors.contains(&agent_c));
     }
 
+    #[test]
+    fn test_get_neighbors_sorted() {
+        let ids: Vec<u32> = (0..40).rev().collect();
+        let topology = TopologyBuilder::star(99, &ids);
+
+        assert_eq!(topology.get_neighbors(99), (0..40).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn test_get_all_connections_sorted() {
+        let topology = Topology::from_edges(&[(4, 2), (0, 3), (2, 1), (3, 1), (0, 4)]);
+        assert_eq!(
+            topology.get_all_connections(),
+            vec![(0, 3), (0, 4), (1, 2), (1, 3), (2, 4)]
+        );
+
+        let directed = TopologyBuilder::directed_from_edges(&[(3, 0), (0, 3), (1, 0)]);
+        assert_eq!(directed.get_all_connections(), vec![(0, 3), (1, 0), (3, 0)]);
+    }
+
+    #[test]
+    fn test_from_edges() {
+        let topology = Topology::from_edges(&[(0, 1), (1, 0), (1, 2), (2, 2)]);
+
+        assert_eq!(topology.connection_count(), 2);
+        assert!(topology.are_connected(0, 1));
+        assert!(topology.are_connected(1, 2));
+    }
+
+    #[test]
+    fn test_shortest_path_len() {
+        let topology = TopologyBuilder::ring(&[0, 1, 2, 3, 4]);
+
+        assert_eq!(topology.shortest_path_len(0, 0), Some(0));
+        assert_eq!(topology.shortest_path_len(0, 1), Some(1));
+        assert_eq!(topology.shortest_path_len(0, 2), Some(2));
+        assert_eq!(topology.shortest_path_len(0, 3), Some(2));
+        assert_eq!(topology.diameter(), Some(2));
+
+        // disconnected pair
+        let mut split = Topology::new();
+        split.add_connection(0, 1);
+        split.add_connection(2, 3);
+        assert_eq!(split.shortest_path_len(0, 3), None);
+        assert_eq!(split.diameter(), Some(1));
+    }
+
+    #[test]
+    fn test_clustering_coefficient() {
+        let triangle = TopologyBuilder::fully_connected(&[0, 1, 2]);
+        for agent in 0..3 {
+            assert_eq!(triangle.clustering_coefficient(agent), 1.0);
+        }
+        assert_eq!(triangle.average_clustering(), 1.0);
+
+        // star has no links between leaves, leaves are excluded from the average
+        let star = TopologyBuilder::star(0, &[1, 2, 3]);
+        assert_eq!(star.clustering_coefficient(0), 0.0);
+        assert_eq!(star.clustering_coefficient(1), 0.0);
+        assert_eq!(star.average_clustering(), 0.0);
+    }
+
+    #[test]
+    fn test_degree_assortativity() {
+        let star = TopologyBuilder::star(0, &[1, 2, 3, 4]);
+        assert!((star.degree_assortativity() - -1.0).abs() < 1e-9);
+
+        // every agent has degree 4
+        let torus = TopologyBuilder::torus(3, 3, &(0..9).collect::<Vec<u32>>());
+        assert!(torus.degree_assortativity().abs() < 1e-9);
+
+        // degrees 1 2 2 1
+        let path = Topology::from_edges(&[(0, 1), (1, 2), (2, 3)]);
+        assert!((path.degree_assortativity() - -0.5).abs() < 1e-9);
+
+        assert_eq!(Topology::new().degree_assortativity(), 0.0);
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let topology = TopologyBuilder::star(2, &[0, 1, 3, 4, 5]);
+
+        let json = serde_json::to_string(&topology).unwrap();
+        let restored: Topology = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(topology, restored);
+    }
+
+    #[test]
+    fn test_merge_ring_with_complement() {
+        let ids = [0, 1, 2, 3, 4, 5];
+        let ring = TopologyBuilder::ring(&ids);
+        let mut complement = Topology::new();
+        for (agent_a, agent_b) in TopologyBuilder::fully_connected(&ids).get_all_connections() {
+            if !ring.are_connected(agent_a, agent_b) {
+                complement.add_connection(agent_a, agent_b);
+            }
+        }
+
+        let union = ring.union(&complement);
+        assert_eq!(union, TopologyBuilder::fully_connected(&ids));
+        assert_eq!(ring.connection_count(), 6);
+
+        // merging again adds nothing
+        let mut merged = union.clone();
+        merged.merge(&ring);
+        assert_eq!(merged.connection_count(), 15);
+    }
+
+    #[test]
+    fn test_equality_ignores_insertion_order() {
+        let forward = Topology::from_edges(&[(0, 1), (1, 2), (2, 3)]);
+        let backward = Topology::from_edges(&[(3, 2), (2, 1), (1, 0)]);
+        assert_eq!(forward, backward);
+
+        let mut lonely = backward.clone();
+        lonely.add_connection(7, 7);
+        lonely.remove_connection(7, 7);
+        assert_eq!(forward, lonely);
+
+        let mut weighted = forward.clone();
+        weighted.add_weighted_connection(0, 1, 0.5);
+        assert_ne!(forward, weighted);
+        assert_ne!(
+            forward,
+            TopologyBuilder::directed_from_edges(&[(0, 1), (1, 2), (2, 3)])
+        );
+    }
+
     #[test]
     fn test_fully_connected() {
         let agents: Vec<u32> = (0..4).collect();
@@ -206,4 +1161,381 @@ mod tests {
         let low_probability = TopologyBuilder::random(&agent_ids, 0.0);
         assert_eq!(low_probability.connection_count(), 0);
     }
+
+    #[test]
+    fn test_random_seeded_reproducible() {
+        let agent_ids: Vec<u32> = (0..10).collect();
+
+        let mut first = TopologyBuilder::random_seeded(&agent_ids, 0.5, 42).get_all_connections();
+        let mut second = TopologyBuilder::random_seeded(&agent_ids, 0.5, 42).get_all_connections();
+        first.sort_unstable();
+        second.sort_unstable();
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_small_world_and_scale_free_seeded_reproducible() {
+        let agent_ids: Vec<u32> = (0..30).collect();
+
+        assert_eq!(
+            TopologyBuilder::small_world_seeded(&agent_ids, 4, 0.5, 42),
+            TopologyBuilder::small_world_seeded(&agent_ids, 4, 0.5, 42)
+        );
+        assert_eq!(
+            TopologyBuilder::scale_free_seeded(&agent_ids, 2, 42),
+            TopologyBuilder::scale_free_seeded(&agent_ids, 2, 42)
+        );
+    }
+
+    #[test]
+    fn test_small_world() {
+        let agent_ids: Vec<u32> = (0..20).collect();
+        let k = 4;
+
+        // no rewiring is a plain lattice, every agent has degree k
+        let lattice = TopologyBuilder::small_world(&agent_ids, k, 0.0);
+        assert_eq!(lattice.connection_count(), agent_ids.len() * k / 2);
+        for agent in &agent_ids {
+            assert_eq!(lattice.get_degree(*agent), k);
+        }
+
+        // rewiring keeps the edge count so the average degree stays k
+        let rewired = TopologyBuilder::small_world(&agent_ids, k, 0.5);
+        assert_eq!(rewired.connection_count(), agent_ids.len() * k / 2);
+        let total_degree: usize = agent_ids.iter().map(|a| rewired.get_degree(*a)).sum();
+        assert_eq!(total_degree, agent_ids.len() * k);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_small_world_k_too_large() {
+        TopologyBuilder::small_world(&[0, 1, 2], 3, 0.1);
+    }
+
+    #[test]
+    fn test_scale_free() {
+        let agent_ids: Vec<u32> = (0..200).collect();
+        let m = 2;
+        let topology = TopologyBuilder::scale_free(&agent_ids, m);
+
+        // seed edges plus m per remaining agent
+        assert_eq!(
+            topology.connection_count(),
+            m * (m - 1) / 2 + (agent_ids.len() - m) * m
+        );
+
+        // hubs should dwarf the typical agent
+        let mut degrees: Vec<usize> = agent_ids.iter().map(|a| topology.get_degree(*a)).collect();
+        degrees.sort_unstable();
+        let median = degrees[degrees.len() / 2];
+        let max = *degrees.last().unwrap();
+        assert!(max >= median * 3, "max degree {} vs median {}", max, median);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_scale_free_m_too_large() {
+        TopologyBuilder::scale_free(&[0, 1, 2], 3);
+    }
+
+    #[test]
+    fn test_grid() {
+        // 3x4 grid
+        // 0  1  2  3
+        // 4  5  6  7
+        // 8  9  10 11
+        let agent_ids: Vec<u32> = (0..12).collect();
+        let topology = TopologyBuilder::grid(3, 4, &agent_ids);
+
+        // corners
+        for corner in [0, 3, 8, 11] {
+            assert_eq!(topology.get_degree(corner), 2);
+        }
+        // edges
+        for edge in [1, 2, 4, 7, 9, 10] {
+            assert_eq!(topology.get_degree(edge), 3);
+        }
+        // interior
+        for interior in [5, 6] {
+            assert_eq!(topology.get_degree(interior), 4);
+        }
+        assert_eq!(topology.connection_count(), 17);
+    }
+
+    #[test]
+    fn test_torus() {
+        let agent_ids: Vec<u32> = (0..12).collect();
+        let topology = TopologyBuilder::torus(3, 4, &agent_ids);
+
+        for agent in &agent_ids {
+            assert_eq!(topology.get_degree(*agent), 4);
+        }
+        assert!(topology.are_connected(0, 3));
+        assert!(topology.are_connected(0, 8));
+    }
+
+    #[test]
+    #[should_panic(expected = "needs exactly 6 agents")]
+    fn test_grid_wrong_agent_count() {
+        TopologyBuilder::grid(2, 3, &[0, 1, 2]);
+    }
+
+    #[test]
+    fn test_ring_small_inputs() {
+        assert_eq!(TopologyBuilder::ring(&[]).connection_count(), 0);
+
+        let single = TopologyBuilder::ring(&[5]);
+        assert_eq!(single.connection_count(), 0);
+        assert!(!single.are_connected(5, 5));
+
+        let pair = TopologyBuilder::ring(&[5, 6]);
+        assert_eq!(pair.get_all_connections(), vec![(5, 6)]);
+        assert_eq!(pair.get_degree(5), 1);
+
+        assert_eq!(TopologyBuilder::ring(&[5, 6, 7]).connection_count(), 3);
+    }
+
+    #[test]
+    fn test_builder_errors() {
+        let out_of_range = |result: Result<Topology, TopologyError>| {
+            matches!(result, Err(TopologyError::ParameterOutOfRange(_)))
+        };
+
+        assert_eq!(
+            TopologyBuilder::try_small_world(&[], 0, 0.1),
+            Err(TopologyError::EmptyAgentList)
+        );
+        assert!(out_of_range(TopologyBuilder::try_small_world(
+            &[0, 1, 2],
+            3,
+            0.1
+        )));
+        assert!(out_of_range(TopologyBuilder::try_small_world(
+            &[0, 1, 2],
+            2,
+            1.5
+        )));
+        assert!(out_of_range(TopologyBuilder::try_small_world(
+            &[0, 1, 2, 3],
+            1,
+            0.1
+        )));
+        assert!(out_of_range(TopologyBuilder::try_small_world(
+            &[0, 1, 2, 3],
+            0,
+            0.1
+        )));
+
+        assert_eq!(
+            TopologyBuilder::try_scale_free(&[], 1),
+            Err(TopologyError::EmptyAgentList)
+        );
+        assert!(out_of_range(TopologyBuilder::try_scale_free(&[0, 1, 2], 0)));
+        assert!(out_of_range(TopologyBuilder::try_scale_free(&[0, 1, 2], 3)));
+        // used to loop forever picking the same id
+        assert_eq!(
+            TopologyBuilder::try_scale_free(&[0, 0, 0], 2),
+            Err(TopologyError::DuplicateAgentIds(vec![0]))
+        );
+        assert_eq!(
+            TopologyBuilder::try_small_world(&[0, 1, 2, 1, 3, 3], 2, 0.1),
+            Err(TopologyError::DuplicateAgentIds(vec![1, 3]))
+        );
+        assert_eq!(
+            TopologyBuilder::try_grid(1, 2, &[4, 4]),
+            Err(TopologyError::DuplicateAgentIds(vec![4]))
+        );
+
+        assert_eq!(
+            TopologyBuilder::try_grid(2, 3, &[0, 1, 2]),
+            Err(TopologyError::WrongAgentCount(6, 3))
+        );
+        assert_eq!(
+            TopologyBuilder::try_torus(0, 0, &[]),
+            Err(TopologyError::EmptyAgentList)
+        );
+
+        assert!(TopologyBuilder::try_scale_free(&[0, 1, 2], 1).is_ok());
+        assert!(TopologyBuilder::try_grid(1, 2, &[0, 1]).is_ok());
+    }
+
+    #[test]
+    fn test_edge_weights() {
+        let mut topology = Topology::new();
+        topology.add_connection(0, 1);
+        topology.add_weighted_connection(1, 2, 2.5);
+
+        assert_eq!(topology.edge_weight(0, 1), Some(1.0));
+        assert_eq!(topology.edge_weight(1, 2), Some(2.5));
+        assert_eq!(topology.edge_weight(2, 1), Some(2.5));
+        assert_eq!(topology.edge_weight(0, 2), None);
+
+        // weights survive a round trip and go away with the connection
+        let json = serde_json::to_string(&topology).unwrap();
+        let restored: Topology = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.edge_weight(2, 1), Some(2.5));
+
+        topology.remove_connection(2, 1);
+        topology.add_connection(1, 2);
+        assert_eq!(topology.edge_weight(1, 2), Some(1.0));
+    }
+
+    #[test]
+    fn test_directed_is_asymmetric() {
+        let mut topology = TopologyBuilder::directed_from_edges(&[(0, 1), (1, 2), (2, 1)]);
+        topology.add_weighted_connection(0, 2, 3.0);
+
+        assert!(topology.is_directed());
+        assert!(topology.are_connected(0, 1));
+        assert!(!topology.are_connected(1, 0));
+        assert_eq!(topology.get_neighbors(0).len(), 2);
+        assert!(topology.get_neighbors(2).contains(&1));
+        assert_eq!(topology.get_degree(2), 1);
+        assert_eq!(topology.edge_weight(0, 2), Some(3.0));
+        assert_eq!(topology.edge_weight(2, 0), None);
+
+        // both directions count as separate connections
+        assert_eq!(topology.connection_count(), 4);
+        assert_eq!(topology.density(), 4.0 / 6.0);
+
+        topology.remove_connection(2, 1);
+        assert!(topology.are_connected(1, 2));
+        assert!(!topology.are_connected(2, 1));
+
+        // in-edges go with the agent too
+        topology.remove_agent(2);
+        assert_eq!(topology.get_all_connections(), vec![(0, 1)]);
+    }
+
+    #[test]
+    fn test_directed_round_trip() {
+        let mut topology = TopologyBuilder::directed_from_edges(&[(0, 1)]);
+        topology.add_weighted_connection(1, 2, 0.5);
+
+        let json = serde_json::to_string(&topology).unwrap();
+        let restored: Topology = serde_json::from_str(&json).unwrap();
+
+        assert!(restored.is_directed());
+        assert!(!restored.are_connected(1, 0));
+        assert_eq!(restored.edge_weight(1, 2), Some(0.5));
+        assert_eq!(restored.edge_weight(2, 1), None);
+    }
+
+    #[test]
+    fn test_connected_components() {
+        let mut topology = TopologyBuilder::ring(&[4, 5, 6]);
+        topology.add_connection(0, 1);
+        topology.add_connection(9, 8);
+
+        assert_eq!(
+            topology.connected_components(),
+            vec![vec![0, 1], vec![4, 5, 6], vec![8, 9]]
+        );
+
+        // a directed edge still joins its ends
+        let directed = TopologyBuilder::directed_from_edges(&[(2, 1), (3, 1)]);
+        assert_eq!(directed.connected_components(), vec![vec![1, 2, 3]]);
+    }
+
+    #[test]
+    fn test_articulation_points() {
+        // two triangles sharing agent 2
+        let bowtie = Topology::from_edges(&[(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (4, 2)]);
+        assert_eq!(bowtie.articulation_points(), vec![2]);
+
+        // every inner agent of a line holds it together, a ring has no weak spot
+        let line = Topology::from_edges(&[(0, 1), (1, 2), (2, 3)]);
+        assert_eq!(line.articulation_points(), vec![1, 2]);
+        assert!(
+            TopologyBuilder::ring(&[0, 1, 2, 3])
+                .articulation_points()
+                .is_empty()
+        );
+        assert_eq!(
+            TopologyBuilder::star(5, &[0, 1, 2]).articulation_points(),
+            vec![5]
+        );
+    }
+
+    #[test]
+    fn test_k_core() {
+        let star = TopologyBuilder::star(0, &[1, 2, 3, 4]);
+        assert_eq!(star.k_core(1).get_all_agent_ids().len(), 5);
+        assert!(star.k_core(2).get_all_agent_ids().is_empty());
+        assert!(star.k_core(3).get_all_agent_ids().is_empty());
+
+        // a triangle with a tail, only the triangle is a 2-core
+        let tailed = Topology::from_edges(&[(0, 1), (1, 2), (2, 0), (2, 3)]);
+        let core = tailed.k_core(2);
+        assert_eq!(core.get_all_agent_ids(), HashSet::from([0, 1, 2]));
+        assert_eq!(core.connection_count(), 3);
+        assert_eq!(tailed.coreness(0), 2);
+        assert_eq!(tailed.coreness(3), 1);
+        assert_eq!(tailed.coreness(42), 0);
+    }
+
+    #[test]
+    fn test_subgraph() {
+        let topology = TopologyBuilder::fully_connected(&[0, 1, 2, 3, 4]);
+
+        let subgraph = topology.subgraph(&HashSet::from([0, 2, 4, 9]));
+
+        assert_eq!(subgraph.connection_count(), 3);
+        assert_eq!(subgraph.get_all_agent_ids(), HashSet::from([0, 2, 4]));
+        assert_eq!(subgraph.get_neighbors(2), vec![0, 4]);
+    }
+
+    #[test]
+    fn test_herd_immunity_threshold() {
+        // every agent has 4 neighbors, r0 = 16 / 4 - 1 = 3
+        let torus = TopologyBuilder::torus(3, 3, &(0..9).collect::<Vec<u32>>());
+        assert!((torus.herd_immunity_threshold() - 2.0 / 3.0).abs() < 1e-12);
+
+        // a ring passes the infection on to a single new agent, it never takes off
+        assert_eq!(
+            TopologyBuilder::ring(&[0, 1, 2, 3]).herd_immunity_threshold(),
+            0.0
+        );
+        assert_eq!(Topology::new().herd_immunity_threshold(), 0.0);
+
+        // hubs make outbreaks easier, a star needs more immunity than its mean degree suggests
+        let star = TopologyBuilder::star(0, &(1..9).collect::<Vec<u32>>());
+        assert!(star.herd_immunity_threshold() > 0.5);
+    }
+
+    #[test]
+    fn test_degree_metrics_fully_connected() {
+        let topology = TopologyBuilder::fully_connected(&[0, 1, 2, 3]);
+
+        assert_eq!(topology.density(), 1.0);
+        assert_eq!(topology.degree_distribution(), HashMap::from([(3, 4)]));
+        assert_eq!(topology.max_degree_node(), Some(0));
+    }
+
+    #[test]
+    fn test_degree_metrics_star() {
+        let topology = TopologyBuilder::star(2, &[0, 1, 3, 4]);
+
+        assert_eq!(topology.max_degree_node(), Some(2));
+        assert_eq!(
+            topology.degree_distribution(),
+            HashMap::from([(4, 1), (1, 4)])
+        );
+        assert_eq!(topology.density(), 0.4);
+        assert_eq!(Topology::new().density(), 0.0);
+        assert_eq!(Topology::new().max_degree_node(), None);
+    }
+
+    #[cfg(feature = "petgraph")]
+    #[test]
+    fn test_to_petgraph() {
+        let ids = [0, 1, 2, 3, 4];
+        let topology = TopologyBuilder::ring(&ids);
+
+        let graph = topology.to_petgraph();
+
+        assert_eq!(graph.node_count(), ids.len());
+        assert_eq!(graph.edge_count(), topology.connection_count());
+    }
 }
@@ -0,0 +1,147 @@
+use core::{GenerationParams, Registry, Topology, TopologyBuilder};
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use debate_engine::{BoxFuture, Completion, LlmProvider, Simulation};
+use genai::chat::ChatMessage;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+// stands in for a real model, every call takes LATENCY so parallelism has something to hide
+const LATENCY: Duration = Duration::from_millis(1);
+
+struct SlowMock;
+
+impl LlmProvider for SlowMock {
+    fn complete<'a>(
+        &'a self,
+        model: &'a str,
+        _messages: &'a [ChatMessage],
+        _params: &'a GenerationParams,
+    ) -> BoxFuture<'a, anyhow::Result<Completion>> {
+        Box::pin(async move {
+            tokio::time::sleep(LATENCY).await;
+            let text = if model == "judge" {
+                "WINNER: PROPOSITION".to_string()
+            } else {
+                format!("argument from {}", model)
+            };
+            Ok(Completion {
+                text,
+                usage: Default::default(),
+            })
+        })
+    }
+}
+
+// agents 0..agents on a seeded random topology with a mean degree of about 6
+fn random_registry(agents: u32) -> Registry {
+    let ids: Vec<u32> = (0..agents).collect();
+    let probability = (6.0 / (agents - 1) as f64).min(1.0);
+    registry_with(
+        agents,
+        TopologyBuilder::random_seeded(&ids, probability, 7),
+        &[0],
+    )
+}
+
+// components random graphs of size agents each, patient zero in every one
+fn disjoint_registry(components: u32, agents: u32) -> Registry {
+    let mut topology = Topology::new();
+    let mut patient_zeros = Vec::new();
+    for component in 0..components {
+        let ids: Vec<u32> = (component * agents..(component + 1) * agents).collect();
+        let probability = (6.0 / (agents - 1) as f64).min(1.0);
+        topology.merge(&TopologyBuilder::random_seeded(&ids, probability, 7));
+        patient_zeros.push(ids[0]);
+    }
+    registry_with(components * agents, topology, &patient_zeros)
+}
+
+fn registry_with(agents: u32, topology: Topology, patient_zeros: &[u32]) -> Registry {
+    let mut registry = Registry::new();
+    for _ in 0..agents {
+        registry.create_agent("model".to_string());
+    }
+    registry.topology = Some(topology);
+    registry.infect_patients_init(patient_zeros).unwrap();
+    registry
+}
+
+fn simulation(parallelism: usize) -> Simulation {
+    Simulation::new("topic", 1, "judge")
+        .with_parallelism(parallelism)
+        .with_seed(7)
+        .with_provider(SlowMock)
+}
+
+// throughput is reported as debates per second
+fn debates(runtime: &Runtime, sim: &Simulation, registry: &Registry, components: bool) -> u64 {
+    let mut registry = registry.clone();
+    let result = runtime.block_on(async {
+        if components {
+            sim.run_components(&mut registry).await
+        } else {
+            sim.run(&mut registry).await
+        }
+    });
+    result.unwrap().rounds as u64
+}
+
+fn bench_parallelism(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let mut group = c.benchmark_group("run");
+    group.sample_size(10);
+
+    for agents in [10, 100, 500] {
+        let registry = random_registry(agents);
+        for parallelism in [1, 8, 32] {
+            let sim = simulation(parallelism);
+            group.throughput(Throughput::Elements(debates(
+                &runtime, &sim, &registry, false,
+            )));
+            group.bench_with_input(
+                BenchmarkId::new(format!("{}_agents", agents), parallelism),
+                &registry,
+                |b, registry| {
+                    b.to_async(&runtime).iter(|| async {
+                        let mut registry = registry.clone();
+                        sim.run(&mut registry).await.unwrap()
+                    })
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+// run_components against the batched run on 5 disjoint components of 100 agents
+fn bench_components(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let registry = disjoint_registry(5, 100);
+    let sim = simulation(8);
+    let mut group = c.benchmark_group("components");
+    group.sample_size(10);
+
+    // each side counts its own debates, under caps the two can stop on different edges
+    group.throughput(Throughput::Elements(debates(
+        &runtime, &sim, &registry, false,
+    )));
+    group.bench_function("run", |b| {
+        b.to_async(&runtime).iter(|| async {
+            let mut registry = registry.clone();
+            sim.run(&mut registry).await.unwrap()
+        })
+    });
+    group.throughput(Throughput::Elements(debates(
+        &runtime, &sim, &registry, true,
+    )));
+    group.bench_function("run_components", |b| {
+        b.to_async(&runtime).iter(|| async {
+            let mut registry = registry.clone();
+            sim.run_components(&mut registry).await.unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_parallelism, bench_components);
+criterion_main!(benches);
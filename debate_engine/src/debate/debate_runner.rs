@@ -1,33 +1,157 @@
-use anyhow::Result;
-use core::{Debate, Exchange, Message};
-use genai::Client;
+use anyhow::{Context, Result};
+use core::{
+    Agent, Debate, DebateOutcome, Exchange, GenerationParams, GroupDebate, GroupExchange,
+    JudgeVote, Message, Role,
+};
 use genai::chat::ChatMessage;
+use std::time::Instant;
 
-use crate::llm::{judge_debate, send_message};
+use crate::debate::format::DebateFormat;
+use crate::debate::prompts::PromptTemplates;
+use crate::llm::{
+    Completion, LlmProvider, RetryPolicy, judge_debate, judge_transcript, send_message,
+    summarize_debate, summary_transcript,
+};
 
+// settings shared by every debate in a run
+#[derive(Debug, Clone)]
+pub struct DebateConfig {
+    pub topic: String,
+    pub max_turns: usize,
+    // every judge votes, the majority decides and a tie leaves the debate ongoing
+    pub judge_models: Vec<String>,
+    // sampling settings for every judge, debaters bring their own
+    pub judge_params: GenerationParams,
+    pub retry: RetryPolicy,
+    pub prompts: PromptTemplates,
+    // the opposer speaks first each round, proposer and opposer keep their sides
+    pub opposer_opens: bool,
+    pub format: DebateFormat,
+    // longer debater replies are cut to this many characters, ellipsis included, before they
+    // are stored, quoted to the other side or shown to the judges
+    pub max_response_chars: Option<usize>,
+    // condense the exchanges with this model first and judge the summary instead, group
+    // debates are always judged on their full threads
+    pub summarizer_model: Option<String>,
+}
+
+impl DebateConfig {
+    pub fn new(topic: impl Into<String>, max_turns: usize, judge_model: impl Into<String>) -> Self {
+        Self {
+            topic: topic.into(),
+            max_turns,
+            judge_models: vec![judge_model.into()],
+            judge_params: GenerationParams::default(),
+            retry: RetryPolicy::default(),
+            prompts: PromptTemplates::default(),
+            opposer_opens: false,
+            format: DebateFormat::default(),
+            max_response_chars: None,
+            summarizer_model: None,
+        }
+    }
+
+    pub fn with_format(mut self, format: DebateFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_max_response_chars(mut self, max_chars: usize) -> Self {
+        self.max_response_chars = Some(max_chars);
+        self
+    }
+
+    pub fn with_summarizer(mut self, summarizer_model: impl Into<String>) -> Self {
+        self.summarizer_model = Some(summarizer_model.into());
+        self
+    }
+
+    // cut a reply down to max_response_chars, the last kept character becomes an ellipsis
+    fn clip(&self, text: &mut String) {
+        if let Some(max_chars) = self.max_response_chars
+            && text.chars().count() > max_chars
+        {
+            *text = text.chars().take(max_chars.saturating_sub(1)).collect();
+            if max_chars > 0 {
+                text.push('…');
+            }
+        }
+    }
+
+    // judge instruction for the topic, with a note on the format
+    fn judge_instruction(&self) -> String {
+        let instruction = PromptTemplates::render(&self.prompts.judge_instruction, &self.topic);
+        match self.format.judge_note() {
+            Some(note) => format!("{} {}", instruction, note),
+            None => instruction,
+        }
+    }
+
+    // reply to the opening speaker's argument, opponent is that speaker's side. plus the
+    // phase when structured
+    fn reply_prompt(&self, argument: &str, opponent: &str, turn: usize) -> String {
+        // argument first so a {topic} inside the argument is left alone
+        let reply = self
+            .prompts
+            .reply
+            .replace("{opponent}", opponent)
+            .replace("{argument}", argument);
+        let reply = PromptTemplates::render(&reply, &self.topic);
+        match self
+            .format
+            .responder_prompt(&self.prompts, turn, self.max_turns)
+        {
+            Some(phase) => format!(
+                "{}\n\n{}",
+                reply,
+                PromptTemplates::render(phase, &self.topic)
+            ),
+            None => reply,
+        }
+    }
+
+    pub fn with_prompts(mut self, prompts: PromptTemplates) -> Self {
+        self.prompts = prompts;
+        self
+    }
+
+    pub fn with_judge_params(mut self, judge_params: GenerationParams) -> Self {
+        self.judge_params = judge_params;
+        self
+    }
+
+    pub fn with_opposer_opening(mut self, opposer_opens: bool) -> Self {
+        self.opposer_opens = opposer_opens;
+        self
+    }
+
+    pub fn with_judges(
+        mut self,
+        judge_models: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.judge_models = judge_models.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+// single entry point for one debate, Simulation and any custom loop both go through here
 pub async fn run_debate(
-    proposer_id: u32,
-    opposer_id: u32,
-    proposer_model: &str,
-    opposer_model: &str,
-    topic: &str,
-    max_turns: usize,
-    judge_model: &str,
+    provider: &dyn LlmProvider,
+    proposer: &Agent,
+    opposer: &Agent,
+    config: &DebateConfig,
 ) -> Result<Debate> {
-    // create genai client
-    let client = Client::default();
+    let topic = config.topic.as_str();
     // init new debate struct
-    let mut debate = Debate::new(proposer_id, opposer_id, max_turns);
+    let mut debate = Debate::new(proposer.id, opposer.id, config.max_turns)
+        .with_topic(topic)
+        .with_models(&proposer.model, &opposer.model);
+    debate.opposer_opened = config.opposer_opens;
 
     // context
-    let proposer_system = format!(
-        "You are debating: '{}'. Your role is PROPOSITION. Be persuasive and logical.",
-        topic
-    );
-    let opposer_system = format!(
-        "You are debating: '{}'. Your role is OPPOSITION. Be persuasive and logical.",
-        topic
-    );
+    let proposer_system = system_prompt(&config.prompts.proposer_system, topic, proposer);
+    let opposer_system = system_prompt(&config.prompts.opposer_system, topic, opposer);
+    let judge_instruction = config.judge_instruction();
 
     // local history
     let mut proposer_history = vec![ChatMessage::system(&proposer_system)];
@@ -36,71 +160,741 @@ pub async fn run_debate(
     let mut message_id = 0;
 
     // 1 turn = 1 proposer message and 1 opposer response
-    for turn in 0..max_turns {
-        let (proposer_response, opposer_response) = run_round(
-            &client,
-            proposer_model,
-            opposer_model,
-            &mut proposer_history,
-            &mut opposer_history,
-            topic,
-            turn,
-        )
-        .await?;
+    for turn in 0..config.max_turns {
+        let ((proposer_response, proposer_latency), (opposer_response, opposer_latency)) =
+            run_round(
+                provider,
+                config,
+                proposer,
+                opposer,
+                &mut proposer_history,
+                &mut opposer_history,
+                turn,
+            )
+            .await?;
+        debate.usage += proposer_response.usage;
+        debate.usage += opposer_response.usage;
+        tracing::debug!(turn, "exchange finished");
 
         // add exchange to the debate
         debate.add_exchange(Exchange {
             proposer: Message {
                 id: message_id,
-                message: proposer_response,
+                role: Role::Proposer,
+                message: proposer_response.text,
+                latency_ms: proposer_latency,
             },
             opposer: Message {
                 id: message_id + 1,
-                message: opposer_response,
+                role: Role::Opposer,
+                message: opposer_response.text,
+                latency_ms: opposer_latency,
             },
         });
 
         message_id += 2;
     }
 
-    // have another model judge the outcome of the interaction
-    let outcome = judge_debate(&client, judge_model, topic, &debate.exchanges).await?;
+    // the judges read either every round or a summary of both cases
+    let transcript = match &config.summarizer_model {
+        Some(summarizer_model) => {
+            let summary = summarize_debate(
+                provider,
+                summarizer_model,
+                topic,
+                &debate.exchanges,
+                &config.judge_params,
+                &config.retry,
+            )
+            .await
+            .with_context(|| format!("summarizer {} failed", summarizer_model))?;
+            debate.usage += summary.usage;
+            let transcript = summary_transcript(topic, &summary.text);
+            debate.summary = Some(summary.text);
+            transcript
+        }
+        None => judge_transcript(topic, &debate.exchanges),
+    };
+
+    // have other models judge the outcome of the interaction
+    for judge_model in &config.judge_models {
+        let verdict = judge_debate(
+            provider,
+            judge_model,
+            &transcript,
+            &judge_instruction,
+            &config.judge_params,
+            &config.retry,
+        )
+        .await
+        .with_context(|| format!("judge {} failed", judge_model))?;
+        tracing::debug!(judge_model = %judge_model, outcome = ?verdict.outcome, "judge voted");
+        debate.usage += verdict.usage;
+        debate.votes.push(JudgeVote {
+            judge_model: judge_model.clone(),
+            outcome: verdict.outcome,
+            rationale: verdict.rationale,
+            confidence: verdict.confidence,
+        });
+    }
+    let outcome = majority_outcome(&debate.votes);
     debate.set_outcome(outcome);
+
+    // reasoning comes from the first judge that sided with the majority
+    if let Some(vote) = debate.votes.iter().find(|vote| vote.outcome == outcome) {
+        debate.rationale = vote.rationale.clone();
+        debate.confidence = vote.confidence;
+    }
     // return updated debate
     Ok(debate)
 }
 
+// one proposer against several opposers in a shared transcript. each round the proposer
+// speaks, then every opposer replies in order seeing the replies before it, and the proposer's
+// next prompt carries all of them. each opposer is judged on its own thread of the transcript so
+// the judges can infect or immunize them independently. the proposer always opens
+pub async fn run_group_debate(
+    provider: &dyn LlmProvider,
+    proposer: &Agent,
+    opposers: &[Agent],
+    config: &DebateConfig,
+) -> Result<GroupDebate> {
+    let topic = config.topic.as_str();
+    let opposer_ids = opposers.iter().map(|opposer| opposer.id).collect();
+    let mut debate = GroupDebate::new(proposer.id, opposer_ids, config.max_turns);
+    debate.topic = topic.to_string();
+
+    // context
+    let proposer_system = system_prompt(&config.prompts.proposer_system, topic, proposer);
+    let judge_instruction = config.judge_instruction();
+
+    // local history, one per participant
+    let mut proposer_history = vec![ChatMessage::system(&proposer_system)];
+    let mut opposer_histories: Vec<Vec<ChatMessage>> = opposers
+        .iter()
+        .map(|opposer| {
+            let opposer_system = system_prompt(&config.prompts.opposer_system, topic, opposer);
+            vec![ChatMessage::system(&opposer_system)]
+        })
+        .collect();
+    let mut message_id = 0;
+
+    for turn in 0..config.max_turns {
+        let template = config
+            .format
+            .opener_prompt(&config.prompts, turn, config.max_turns, false);
+        let prompt = PromptTemplates::render(template, topic);
+        let prompt = match debate.exchanges.last() {
+            Some(last) => format!("{}\n\n{}", group_replies(&last.opposers), prompt),
+            None => prompt,
+        };
+
+        proposer_history.push(ChatMessage::user(&prompt));
+        let (mut proposer_response, proposer_latency) = timed(send_message(
+            provider,
+            &proposer.model,
+            &proposer_history,
+            &proposer.gen_params,
+            &config.retry,
+        ))
+        .await
+        .with_context(|| failed_on(proposer, turn))?;
+        config.clip(&mut proposer_response.text);
+        proposer_history.push(ChatMessage::assistant(&proposer_response.text));
+        debate.usage += proposer_response.usage;
+        let argument = proposer_response.text;
+        let argument_id = message_id;
+        message_id += 1;
+
+        let mut replies: Vec<(u32, Message)> = Vec::new();
+        for (opposer, history) in opposers.iter().zip(&mut opposer_histories) {
+            let prompt = config.reply_prompt(&argument, "PROPOSITION", turn);
+            let prompt = if replies.is_empty() {
+                prompt
+            } else {
+                format!("{}\n\n{}", group_replies(&replies), prompt)
+            };
+
+            history.push(ChatMessage::user(prompt));
+            let (mut response, latency_ms) = timed(send_message(
+                provider,
+                &opposer.model,
+                history,
+                &opposer.gen_params,
+                &config.retry,
+            ))
+            .await
+            .with_context(|| failed_on(opposer, turn))?;
+            config.clip(&mut response.text);
+            history.push(ChatMessage::assistant(&response.text));
+            debate.usage += response.usage;
+
+            replies.push((
+                opposer.id,
+                Message {
+                    id: message_id,
+                    role: Role::Opposer,
+                    message: response.text,
+                    latency_ms,
+                },
+            ));
+            message_id += 1;
+        }
+
+        debate.exchanges.push(GroupExchange {
+            proposer: Message {
+                id: argument_id,
+                role: Role::Proposer,
+                message: argument,
+                latency_ms: proposer_latency,
+            },
+            opposers: replies,
+        });
+    }
+
+    // every judge votes on every opposer's thread
+    for opposer in opposers {
+        let exchanges = debate.exchanges_with(opposer.id);
+        let mut votes = Vec::new();
+        for judge_model in &config.judge_models {
+            let verdict = judge_debate(
+                provider,
+                judge_model,
+                &judge_transcript(topic, &exchanges),
+                &judge_instruction,
+                &config.judge_params,
+                &config.retry,
+            )
+            .await
+            .with_context(|| format!("judge {} failed", judge_model))?;
+            debate.usage += verdict.usage;
+            votes.push(JudgeVote {
+                judge_model: judge_model.clone(),
+                outcome: verdict.outcome,
+                rationale: verdict.rationale,
+                confidence: verdict.confidence,
+            });
+        }
+        debate.outcomes.push((opposer.id, majority_outcome(&votes)));
+    }
+
+    Ok(debate)
+}
+
+// other opposers' replies, quoted for the next speaker
+fn group_replies(replies: &[(u32, Message)]) -> String {
+    replies
+        .iter()
+        .map(|(id, reply)| format!("OPPOSITION (agent {}) said: '{}'", id, reply.message))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+// side with more votes wins, a tie (or no judges) leaves the debate ongoing
+fn majority_outcome(votes: &[JudgeVote]) -> DebateOutcome {
+    let count = |outcome| votes.iter().filter(|vote| vote.outcome == outcome).count();
+    let proposer_votes = count(DebateOutcome::ProposerWon);
+    let opposer_votes = count(DebateOutcome::OpposerWon);
+
+    match proposer_votes.cmp(&opposer_votes) {
+        std::cmp::Ordering::Greater => DebateOutcome::ProposerWon,
+        std::cmp::Ordering::Less => DebateOutcome::OpposerWon,
+        std::cmp::Ordering::Equal => DebateOutcome::Ongoing,
+    }
+}
+
 // priv func
+// a response and how long it took in milliseconds
+type Timed = (Completion, u64);
+
+// measures a request including any retries and backoff
+async fn timed(request: impl Future<Output = Result<Completion>>) -> Result<Timed> {
+    let started = Instant::now();
+    let completion = request.await?;
+    Ok((completion, started.elapsed().as_millis() as u64))
+}
+
+// rendered side prompt with the agent's persona after it
+fn system_prompt(template: &str, topic: &str, agent: &Agent) -> String {
+    let system = PromptTemplates::render(template, topic);
+    match &agent.persona {
+        Some(persona) => format!("{} Your persona: {}.", system, persona),
+        None => system,
+    }
+}
+
+// turns count from 1 in error messages
+fn failed_on(agent: &Agent, turn: usize) -> String {
+    format!(
+        "agent {} ({}) failed on turn {}",
+        agent.id,
+        agent.model,
+        turn + 1
+    )
+}
+
 async fn run_round(
-    client: &Client,
-    proposer_model: &str,
-    opposer_model: &str,
+    provider: &dyn LlmProvider,
+    config: &DebateConfig,
+    proposer: &Agent,
+    opposer: &Agent,
     proposer_history: &mut Vec<ChatMessage>,
     opposer_history: &mut Vec<ChatMessage>,
-    topic: &str,
     turn: usize,
-) -> Result<(String, String)> {
+) -> Result<(Timed, Timed)> {
     // more context
-    let prompt = if turn == 0 {
-        format!("Make your opening argument for: '{}'", topic)
-    } else {
-        "Continue your argument. Address opponent's points.".to_string()
-    };
+    let template = config.format.opener_prompt(
+        &config.prompts,
+        turn,
+        config.max_turns,
+        config.opposer_opens,
+    );
+    let prompt = PromptTemplates::render(template, &config.topic);
 
-    // push proposer history
-    proposer_history.push(ChatMessage::user(&prompt));
-    let proposer_response = send_message(client, proposer_model, proposer_history).await?;
-    proposer_history.push(ChatMessage::assistant(&proposer_response));
+    // the proposer opens unless the config hands the opening to the opposer
+    let ((opener, opener_history), (responder, responder_history), opener_side) =
+        if config.opposer_opens {
+            (
+                (opposer, opposer_history),
+                (proposer, proposer_history),
+                "OPPOSITION",
+            )
+        } else {
+            (
+                (proposer, proposer_history),
+                (opposer, opposer_history),
+                "PROPOSITION",
+            )
+        };
 
-    // push opposer history
-    opposer_history.push(ChatMessage::user(format!(
-        "PROPOSITION said: '{}'\n\nRespond and defend your position.",
-        proposer_response
+    // push opener history
+    opener_history.push(ChatMessage::user(&prompt));
+    let mut opener_response = timed(send_message(
+        provider,
+        &opener.model,
+        opener_history,
+        &opener.gen_params,
+        &config.retry,
+    ))
+    .await
+    .with_context(|| failed_on(opener, turn))?;
+    config.clip(&mut opener_response.0.text);
+    opener_history.push(ChatMessage::assistant(&opener_response.0.text));
+
+    // push responder history
+    responder_history.push(ChatMessage::user(config.reply_prompt(
+        &opener_response.0.text,
+        opener_side,
+        turn,
     )));
 
-    let opposer_response = send_message(client, opposer_model, opposer_history).await?;
-    opposer_history.push(ChatMessage::assistant(&opposer_response));
+    let mut responder_response = timed(send_message(
+        provider,
+        &responder.model,
+        responder_history,
+        &responder.gen_params,
+        &config.retry,
+    ))
+    .await
+    .with_context(|| failed_on(responder, turn))?;
+    config.clip(&mut responder_response.0.text);
+    responder_history.push(ChatMessage::assistant(&responder_response.0.text));
+
+    // return both responses, proposer first
+    if config.opposer_opens {
+        Ok((responder_response, opener_response))
+    } else {
+        Ok((opener_response, responder_response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockProvider, block_on, text};
+    use core::TokenUsage;
+
+    fn agent(id: u32, model: &str) -> Agent {
+        Agent::new(id, model.to_string())
+    }
+
+    #[test]
+    fn test_run_debate_with_mock() {
+        let provider = MockProvider::judge_always("OPPOSITION");
+        let config = DebateConfig::new("pineapple on pizza", 2, "judge");
+
+        let debate = block_on(run_debate(
+            &provider,
+            &agent(0, "strong"),
+            &agent(1, "weak"),
+            &config,
+        ))
+        .unwrap();
 
-    // return both responses
-    Ok((proposer_response, opposer_response))
+        assert_eq!(debate.exchanges.len(), 2);
+        assert_eq!(debate.topic, "pineapple on pizza");
+        assert_eq!(debate.proposer_model, "strong");
+        assert_eq!(debate.opposer_model, "weak");
+        assert_eq!(debate.exchanges[0].proposer.message, "argument from strong");
+        assert_eq!(debate.exchanges[0].opposer.message, "argument from weak");
+        assert_eq!(debate.outcome, DebateOutcome::OpposerWon);
+
+        // 2 messages per turn plus the judge
+        let calls = provider.calls();
+        assert_eq!(calls.len(), 5);
+        assert_eq!(calls[4].0, "judge");
+        assert!(text(&calls[0].1[0]).contains("pineapple on pizza"));
+    }
+
+    #[test]
+    fn test_run_debate_records_latency() {
+        // the slow model blocks inside the mock before replying
+        let provider = MockProvider::new(|model, _| {
+            if model == "slow" {
+                std::thread::sleep(std::time::Duration::from_millis(20));
+            }
+            Ok(if model == "judge" {
+                "WINNER: PROPOSITION".to_string()
+            } else {
+                "argument".to_string()
+            })
+        });
+        let config = DebateConfig::new("topic", 1, "judge");
+
+        let debate = block_on(run_debate(
+            &provider,
+            &agent(0, "slow"),
+            &agent(1, "fast"),
+            &config,
+        ))
+        .unwrap();
+
+        assert!(debate.exchanges[0].proposer.latency_ms >= 20);
+        assert!(debate.exchanges[0].opposer.latency_ms < 20);
+    }
+
+    #[test]
+    fn test_long_replies_are_clipped() {
+        let provider = MockProvider::new(|model, _| {
+            Ok(if model == "judge" {
+                "WINNER: PROPOSITION".to_string()
+            } else {
+                "word ".repeat(100)
+            })
+        });
+        let config = DebateConfig::new("topic", 2, "judge").with_max_response_chars(12);
+
+        let debate = block_on(run_debate(
+            &provider,
+            &agent(0, "a"),
+            &agent(1, "b"),
+            &config,
+        ))
+        .unwrap();
+
+        let exchange = &debate.exchanges[0];
+        assert_eq!(exchange.proposer.message, "word word w…");
+        assert_eq!(exchange.opposer.message.chars().count(), 12);
+
+        // the opposer was quoted the clipped argument, and so was the judge
+        let calls = provider.calls();
+        assert!(text(calls[1].1.last().unwrap()).contains("word word w…"));
+        let judge_prompt = text(calls.last().unwrap().1.last().unwrap());
+        assert!(!judge_prompt.contains(&"word ".repeat(3)));
+    }
+
+    // judges named after the side they always pick
+    fn partisan_judges() -> MockProvider {
+        MockProvider::new(|model, _| match model {
+            "pro" | "pro2" => Ok("WINNER: PROPOSITION\nREASON: pro".to_string()),
+            "opp" => Ok("WINNER: OPPOSITION\nREASON: opp".to_string()),
+            _ => Ok("argument".to_string()),
+        })
+    }
+
+    #[test]
+    fn test_judges_majority_vote() {
+        let provider = partisan_judges();
+        let config = DebateConfig::new("topic", 1, "pro").with_judges(["opp", "pro", "pro2"]);
+
+        let debate = block_on(run_debate(
+            &provider,
+            &agent(0, "a"),
+            &agent(1, "b"),
+            &config,
+        ))
+        .unwrap();
+
+        assert_eq!(debate.outcome, DebateOutcome::ProposerWon);
+        assert_eq!(debate.votes.len(), 3);
+        assert_eq!(debate.votes[0].judge_model, "opp");
+        assert_eq!(debate.votes[0].outcome, DebateOutcome::OpposerWon);
+        assert_eq!(debate.rationale.as_deref(), Some("pro"));
+    }
+
+    #[test]
+    fn test_judges_tie_is_ongoing() {
+        let provider = partisan_judges();
+        let config = DebateConfig::new("topic", 1, "pro").with_judges(["pro", "opp"]);
+
+        let debate = block_on(run_debate(
+            &provider,
+            &agent(0, "a"),
+            &agent(1, "b"),
+            &config,
+        ))
+        .unwrap();
+
+        assert_eq!(debate.outcome, DebateOutcome::Ongoing);
+        assert_eq!(debate.rationale, None);
+    }
+
+    #[test]
+    fn test_custom_prompts_reach_provider() {
+        let provider = MockProvider::judge_always("PROPOSITION");
+        let prompts = PromptTemplates {
+            proposer_system: "Argue for {topic} like a pirate.".to_string(),
+            judge_instruction: "Judge the {topic} debate harshly.".to_string(),
+            ..PromptTemplates::default()
+        };
+        let config = DebateConfig::new("tabs", 1, "judge").with_prompts(prompts);
+
+        block_on(run_debate(
+            &provider,
+            &agent(0, "a"),
+            &agent(1, "b"),
+            &config,
+        ))
+        .unwrap();
+
+        let calls = provider.calls();
+        assert_eq!(text(&calls[0].1[0]), "Argue for tabs like a pirate.");
+        assert!(text(&calls[2].1[0]).starts_with("Judge the tabs debate harshly."));
+    }
+
+    #[test]
+    fn test_persona_reaches_system_prompt() {
+        let provider = MockProvider::judge_always("PROPOSITION");
+        let config = DebateConfig::new("tabs", 1, "judge");
+        let contrarian = agent(1, "b").with_persona("aggressive contrarian");
+
+        block_on(run_debate(&provider, &agent(0, "a"), &contrarian, &config)).unwrap();
+
+        let calls = provider.calls();
+        assert_eq!(
+            text(&calls[0].1[0]),
+            "You are debating: 'tabs'. Your role is PROPOSITION. Be persuasive and logical."
+        );
+        assert_eq!(calls[1].0, "b");
+        assert_eq!(
+            text(&calls[1].1[0]),
+            "You are debating: 'tabs'. Your role is OPPOSITION. Be persuasive and logical. \
+             Your persona: aggressive contrarian."
+        );
+    }
+
+    #[test]
+    fn test_judges_read_the_summary() {
+        let provider = MockProvider::new(|model, _| match model {
+            "summarizer" => Ok("PROPOSITION:\n- tabs align\nOPPOSITION:\n- spaces render".into()),
+            "judge" => Ok("WINNER: OPPOSITION".to_string()),
+            _ => Ok(format!("argument from {}", model)),
+        });
+        let config = DebateConfig::new("tabs", 2, "judge").with_summarizer("summarizer");
+
+        let debate = block_on(run_debate(
+            &provider,
+            &agent(0, "a"),
+            &agent(1, "b"),
+            &config,
+        ))
+        .unwrap();
+
+        let calls = provider.calls();
+        assert_eq!(calls.len(), 6);
+        assert_eq!(calls[4].0, "summarizer");
+        assert!(text(&calls[4].1[1]).contains("argument from a"));
+
+        let (judge, messages, _) = &calls[5];
+        assert_eq!(judge, "judge");
+        let read = text(&messages[1]);
+        assert!(read.contains("- tabs align"));
+        assert!(!read.contains("argument from"));
+
+        assert_eq!(
+            debate.summary.as_deref(),
+            Some("PROPOSITION:\n- tabs align\nOPPOSITION:\n- spaces render")
+        );
+        assert_eq!(debate.outcome, DebateOutcome::OpposerWon);
+    }
+
+    #[test]
+    fn test_gen_params_reach_provider() {
+        let provider = MockProvider::judge_always("PROPOSITION");
+        let judge_params = GenerationParams::default().with_temperature(0.0);
+        let config = DebateConfig::new("topic", 1, "judge").with_judge_params(judge_params);
+        let hot = agent(0, "hot").with_gen_params(
+            GenerationParams::default()
+                .with_temperature(1.2)
+                .with_max_tokens(300),
+        );
+
+        block_on(run_debate(&provider, &hot, &agent(1, "calm"), &config)).unwrap();
+
+        let calls = provider.calls();
+        assert_eq!(calls[0].2.temperature, Some(1.2));
+        assert_eq!(calls[0].2.max_tokens, Some(300));
+        assert_eq!(calls[1].2, GenerationParams::default());
+        assert_eq!(calls[2].2, judge_params);
+    }
+
+    #[test]
+    fn test_opposer_opening() {
+        let provider = MockProvider::judge_always("PROPOSITION");
+        let config = DebateConfig::new("topic", 1, "judge").with_opposer_opening(true);
+
+        let debate = block_on(run_debate(
+            &provider,
+            &agent(0, "pro"),
+            &agent(1, "opp"),
+            &config,
+        ))
+        .unwrap();
+
+        // the opposer speaks first, the proposer replies to its argument
+        let calls = provider.calls();
+        assert_eq!(calls[0].0, "opp");
+        assert_eq!(
+            text(calls[0].1.last().unwrap()),
+            "Make your opening argument against: 'topic'"
+        );
+        assert_eq!(calls[1].0, "pro");
+        assert_eq!(
+            text(calls[1].1.last().unwrap()),
+            "OPPOSITION said: 'argument from opp'\n\nRespond and defend your position."
+        );
+        assert!(debate.opposer_opened);
+        assert_eq!(debate.exchanges[0].proposer.message, "argument from pro");
+        assert_eq!(debate.exchanges[0].opposer.message, "argument from opp");
+    }
+
+    #[test]
+    fn test_group_debate_judges_each_opposer() {
+        // the judge sides with the tough opposer and against everyone else
+        let provider = MockProvider::new(|model, messages| {
+            if model != "judge" {
+                return Ok(format!("argument from {}", model));
+            }
+            let transcript: String = messages.iter().map(text).collect();
+            if transcript.contains("OPPOSITION: argument from tough") {
+                Ok("WINNER: OPPOSITION".to_string())
+            } else {
+                Ok("WINNER: PROPOSITION".to_string())
+            }
+        });
+        let config = DebateConfig::new("topic", 2, "judge");
+        let opposers = [agent(1, "tough"), agent(2, "soft")];
+
+        let debate = block_on(run_group_debate(
+            &provider,
+            &agent(0, "pro"),
+            &opposers,
+            &config,
+        ))
+        .unwrap();
+
+        assert_eq!(debate.opposer_ids, vec![1, 2]);
+        assert_eq!(debate.exchanges.len(), 2);
+        assert_eq!(debate.exchanges[0].opposers.len(), 2);
+        assert_eq!(debate.outcome_for(1), Some(DebateOutcome::OpposerWon));
+        assert_eq!(debate.outcome_for(2), Some(DebateOutcome::ProposerWon));
+
+        // the second opposer hears the first, the proposer hears both next round
+        let calls = provider.calls();
+        assert!(text(calls[2].1.last().unwrap()).contains("argument from tough"));
+        let next_round = text(calls[3].1.last().unwrap());
+        assert!(
+            next_round.contains("argument from tough") && next_round.contains("argument from soft")
+        );
+        // 3 speakers over 2 rounds plus one judge per opposer
+        assert_eq!(calls.len(), 8);
+    }
+
+    #[test]
+    fn test_group_debate_errors_name_the_model() {
+        let failing = |broken: &'static str| {
+            let provider = MockProvider::new(move |model, _| match model {
+                _ if model == broken => Err(anyhow::anyhow!("No response from model")),
+                "judge" => Ok("WINNER: PROPOSITION".to_string()),
+                _ => Ok(format!("argument from {}", model)),
+            });
+            let opposers = [agent(1, "tough"), agent(2, "soft")];
+            let err = block_on(run_group_debate(
+                &provider,
+                &agent(0, "pro"),
+                &opposers,
+                &DebateConfig::new("topic", 2, "judge"),
+            ))
+            .unwrap_err();
+            format!("{:#}", err)
+        };
+
+        assert_eq!(
+            failing("soft"),
+            "agent 2 (soft) failed on turn 1: No response from model"
+        );
+        assert_eq!(
+            failing("pro"),
+            "agent 0 (pro) failed on turn 1: No response from model"
+        );
+        assert_eq!(
+            failing("judge"),
+            "judge judge failed: No response from model"
+        );
+    }
+
+    #[test]
+    fn test_structured_format_prompts() {
+        let provider = MockProvider::judge_always("PROPOSITION");
+        let config = DebateConfig::new("tabs", 3, "judge").with_format(DebateFormat::Structured);
+
+        block_on(run_debate(
+            &provider,
+            &agent(0, "a"),
+            &agent(1, "b"),
+            &config,
+        ))
+        .unwrap();
+
+        let calls = provider.calls();
+        let last = |call: usize| text(calls[call].1.last().unwrap());
+        assert!(last(0).starts_with("Make your opening argument"));
+        assert!(last(2).starts_with("Give your rebuttal"));
+        assert!(
+            last(3).ends_with("Give your rebuttal. Take apart your opponent's strongest points.")
+        );
+        assert_eq!(
+            last(4),
+            "Give your closing statement. Summarize why your side of 'tabs' wins."
+        );
+        assert!(last(5).ends_with("Summarize why your side of 'tabs' wins."));
+        assert!(text(&calls[6].1[0]).contains("opening statements, rebuttals and closing"));
+    }
+
+    #[test]
+    fn test_usage_sums_every_request() {
+        let provider = MockProvider::judge_always("PROPOSITION").with_usage(TokenUsage::new(10, 5));
+        let config = DebateConfig::new("topic", 2, "judge");
+
+        let debate = block_on(run_debate(
+            &provider,
+            &agent(0, "a"),
+            &agent(1, "b"),
+            &config,
+        ))
+        .unwrap();
+
+        // 4 debater messages and the judge
+        assert_eq!(debate.usage, TokenUsage::new(50, 25));
+    }
 }
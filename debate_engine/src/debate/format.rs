@@ -0,0 +1,111 @@
+use crate::debate::prompts::PromptTemplates;
+
+// how the rounds of a debate are prompted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DebateFormat {
+    // opening prompt, then the same turn prompt every round
+    #[default]
+    FreeForm,
+    // opening statements on the first turn, closing statements on the last and rebuttals in
+    // between, for both sides
+    Structured,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    Opening,
+    Rebuttal,
+    Closing,
+}
+
+// a single turn debate only has openings
+fn phase(turn: usize, max_turns: usize) -> Phase {
+    if turn == 0 {
+        Phase::Opening
+    } else if turn + 1 == max_turns {
+        Phase::Closing
+    } else {
+        Phase::Rebuttal
+    }
+}
+
+impl DebateFormat {
+    // prompt for whoever speaks first in a round, the opening depends on which side that is
+    pub(crate) fn opener_prompt<'a>(
+        &self,
+        prompts: &'a PromptTemplates,
+        turn: usize,
+        max_turns: usize,
+        opposer_opens: bool,
+    ) -> &'a str {
+        match (self, phase(turn, max_turns)) {
+            (_, Phase::Opening) if opposer_opens => &prompts.opposer_opening,
+            (_, Phase::Opening) => &prompts.opening,
+            (DebateFormat::FreeForm, _) => &prompts.turn,
+            (DebateFormat::Structured, Phase::Rebuttal) => &prompts.rebuttal,
+            (DebateFormat::Structured, Phase::Closing) => &prompts.closing,
+        }
+    }
+
+    // appended to the reply prompt so the responder knows the phase too, none when free form
+    pub(crate) fn responder_prompt<'a>(
+        &self,
+        prompts: &'a PromptTemplates,
+        turn: usize,
+        max_turns: usize,
+    ) -> Option<&'a str> {
+        match (self, phase(turn, max_turns)) {
+            (DebateFormat::FreeForm, _) | (_, Phase::Opening) => None,
+            (DebateFormat::Structured, Phase::Rebuttal) => Some(&prompts.rebuttal),
+            (DebateFormat::Structured, Phase::Closing) => Some(&prompts.closing),
+        }
+    }
+
+    // appended to the judge instruction
+    pub(crate) fn judge_note(&self) -> Option<&'static str> {
+        match self {
+            DebateFormat::FreeForm => None,
+            DebateFormat::Structured => Some(
+                "The debate was structured as opening statements, rebuttals and closing \
+                 statements, weigh how well each side rebutted and closed.",
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_structured_phases() {
+        let prompts = PromptTemplates::default();
+        let format = DebateFormat::Structured;
+        let opener: Vec<&str> = (0..3)
+            .map(|turn| format.opener_prompt(&prompts, turn, 3, false))
+            .collect();
+
+        assert_eq!(
+            opener,
+            vec![&prompts.opening, &prompts.rebuttal, &prompts.closing]
+        );
+        assert_eq!(
+            format.opener_prompt(&prompts, 0, 3, true),
+            prompts.opposer_opening
+        );
+        assert_eq!(format.responder_prompt(&prompts, 0, 3), None);
+        assert_eq!(
+            format.responder_prompt(&prompts, 2, 3),
+            Some(prompts.closing.as_str())
+        );
+        // free form keeps repeating the turn prompt
+        assert_eq!(
+            DebateFormat::FreeForm.opener_prompt(&prompts, 2, 3, false),
+            prompts.turn
+        );
+        assert_eq!(
+            DebateFormat::FreeForm.responder_prompt(&prompts, 2, 3),
+            None
+        );
+    }
+}
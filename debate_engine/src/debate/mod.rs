@@ -1 +1,3 @@
 pub mod debate_runner;
+pub mod format;
+pub mod prompts;
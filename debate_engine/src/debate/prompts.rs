@@ -0,0 +1,62 @@
+// every prompt sent during a debate, {topic} is replaced with the debate topic
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptTemplates {
+    pub proposer_system: String,
+    pub opposer_system: String,
+    // first user prompt for the proposer
+    pub opening: String,
+    // first user prompt for the opposer when it opens, see DebateConfig::with_opposer_opening
+    pub opposer_opening: String,
+    // user prompt for the proposer on every later turn
+    pub turn: String,
+    // structured debates only, middle and last turns for both sides
+    pub rebuttal: String,
+    pub closing: String,
+    // user prompt for whoever speaks second, {argument} is the opener's latest message and
+    // {opponent} the opener's side, PROPOSITION or OPPOSITION
+    pub reply: String,
+    // what the judge is asked to weigh, the WINNER / CONFIDENCE / REASON format is always appended
+    pub judge_instruction: String,
+}
+
+impl PromptTemplates {
+    // fill in the {topic} placeholder
+    pub fn render(template: &str, topic: &str) -> String {
+        template.replace("{topic}", topic)
+    }
+}
+
+impl Default for PromptTemplates {
+    fn default() -> Self {
+        Self {
+            proposer_system:
+                "You are debating: '{topic}'. Your role is PROPOSITION. Be persuasive and logical."
+                    .to_string(),
+            opposer_system:
+                "You are debating: '{topic}'. Your role is OPPOSITION. Be persuasive and logical."
+                    .to_string(),
+            opening: "Make your opening argument for: '{topic}'".to_string(),
+            opposer_opening: "Make your opening argument against: '{topic}'".to_string(),
+            turn: "Continue your argument. Address opponent's points.".to_string(),
+            rebuttal: "Give your rebuttal. Take apart your opponent's strongest points."
+                .to_string(),
+            closing: "Give your closing statement. Summarize why your side of '{topic}' wins."
+                .to_string(),
+            reply: "{opponent} said: '{argument}'\n\nRespond and defend your position.".to_string(),
+            judge_instruction: "Evaluate this debate.".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_replaces_every_topic() {
+        assert_eq!(
+            PromptTemplates::render("{topic}? Argue against {topic}.", "tabs"),
+            "tabs? Argue against tabs."
+        );
+    }
+}
@@ -1,5 +1,15 @@
 pub mod debate;
 mod llm;
 pub mod simulation;
-pub use debate::debate_runner::run_debate;
-pub use simulation::engine::{Simulation, SimulationResult};
+#[cfg(test)]
+mod test_support;
+pub use debate::debate_runner::{DebateConfig, run_debate, run_group_debate};
+pub use debate::format::DebateFormat;
+pub use debate::prompts::PromptTemplates;
+pub use llm::{BoxFuture, Completion, GenaiProvider, LlmProvider, RetryPolicy, TransientError};
+pub use simulation::cache::{CacheKey, DebateCache};
+pub use simulation::config::{AgentConfig, SimulationConfig, TopologyConfig};
+pub use simulation::engine::{Simulation, SimulationResult, TerminationReason};
+pub use simulation::events::SimulationEvent;
+pub use simulation::progress::{NoProgress, ProgressReporter};
+pub use simulation::tournament::{Standing, TournamentResult};
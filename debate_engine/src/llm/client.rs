@@ -1,35 +1,151 @@
+use crate::llm::{Completion, LlmProvider};
 use anyhow::Result;
-use core::{DebateOutcome, Exchange};
-use genai::Client;
-use genai::chat::{ChatMessage, ChatRequest};
+use core::{DebateOutcome, Exchange, GenerationParams, TokenUsage};
+use genai::chat::ChatMessage;
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
 
-// genai send message helper
+// how often and how patiently to retry transient provider failures
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    // total attempts including the first, 1 disables retrying
+    pub max_attempts: usize,
+    // delay before the first retry, doubled for each one after
+    pub base_delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: usize, base_delay: Duration) -> Self {
+        Self {
+            max_attempts,
+            base_delay,
+        }
+    }
+
+    pub fn none() -> Self {
+        Self::new(1, Duration::ZERO)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(3, Duration::from_millis(500))
+    }
+}
+
+// error a caller can return to mark a failure as worth retrying
+#[derive(Debug)]
+pub struct TransientError(pub String);
+
+impl fmt::Display for TransientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "transient error: {}", self.0)
+    }
+}
+
+impl std::error::Error for TransientError {}
+
+// rate limits, server errors and dropped connections, anything else fails straight away
+pub fn is_transient(err: &anyhow::Error) -> bool {
+    if err.downcast_ref::<TransientError>().is_some() {
+        return true;
+    }
+
+    let transient_status = |status: u16| status == 429 || (500..600).contains(&status);
+    let transient_webc = |webc_error: &genai::webc::Error| match webc_error {
+        genai::webc::Error::ResponseFailedStatus { status, .. } => {
+            transient_status(status.as_u16())
+        }
+        genai::webc::Error::Reqwest(e) => e.is_timeout() || e.is_connect(),
+        _ => false,
+    };
+
+    match err.downcast_ref::<genai::Error>() {
+        Some(genai::Error::WebModelCall { webc_error, .. })
+        | Some(genai::Error::WebAdapterCall { webc_error, .. }) => transient_webc(webc_error),
+        Some(genai::Error::HttpError { status, .. }) => transient_status(status.as_u16()),
+        _ => false,
+    }
+}
+
+// run op until it succeeds, fails with a non transient error or runs out of attempts
+pub async fn retry_with_backoff<T, F, Fut>(policy: &RetryPolicy, mut op: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut delay = policy.base_delay;
+    let mut attempt = 1;
+
+    loop {
+        match op().await {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt < policy.max_attempts && is_transient(&err) => {
+                tracing::warn!(attempt, error = %err, ?delay, "transient provider error, retrying");
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+// send message helper, retries transient provider errors
 pub async fn send_message(
-    client: &Client,
+    provider: &dyn LlmProvider,
     model: &str,
     messages: &[ChatMessage],
-) -> Result<String> {
-    let chat_req = ChatRequest::new(messages.to_vec());
-    let chat_res = client.exec_chat(model, chat_req, None).await?;
+    params: &GenerationParams,
+    retry: &RetryPolicy,
+) -> Result<Completion> {
+    retry_with_backoff(retry, || provider.complete(model, messages, params)).await
+}
 
-    chat_res
-        .first_text()
-        .ok_or_else(|| anyhow::anyhow!("No response from model"))
-        .map(|s| s.to_string())
+// parsed judge response
+#[derive(Debug, Clone, PartialEq)]
+pub struct JudgeVerdict {
+    pub outcome: DebateOutcome,
+    pub rationale: Option<String>,
+    pub confidence: Option<f32>,
+    // tokens spent on the judge request
+    pub usage: TokenUsage,
 }
 
 // judge debate
+// transcript is what the judge reads, see judge_transcript and summary_transcript
 pub async fn judge_debate(
-    client: &Client,
+    provider: &dyn LlmProvider,
     judge_model: &str,
-    topic: &str,
-    exchanges: &[Exchange],
-) -> Result<DebateOutcome> {
-    let mut messages = vec![ChatMessage::system(
-        "Evaluate this debate. Respond with EXACTLY:\n\
-         WINNER: PROPOSITION\nor\nWINNER: OPPOSITION",
-    )];
+    transcript: &str,
+    instruction: &str,
+    params: &GenerationParams,
+    retry: &RetryPolicy,
+) -> Result<JudgeVerdict> {
+    // parse_verdict relies on this format, so callers only choose the instruction before it
+    let mut messages = vec![ChatMessage::system(format!(
+        "{} Respond with EXACTLY:\n\
+         WINNER: PROPOSITION or OPPOSITION\n\
+         CONFIDENCE: a number between 0.0 and 1.0\n\
+         REASON: one or two sentences explaining the verdict",
+        instruction
+    ))];
 
+    messages.push(ChatMessage::user(transcript));
+    messages.push(ChatMessage::user(
+        "Who won? Answer in the WINNER / CONFIDENCE / REASON format.",
+    ));
+
+    let completion = send_message(provider, judge_model, &messages, params, retry).await?;
+
+    let mut verdict = parse_verdict(&completion.text)?;
+    verdict.usage = completion.usage;
+    Ok(verdict)
+}
+
+// every round of the debate, the judges' default view
+pub fn judge_transcript(topic: &str, exchanges: &[Exchange]) -> String {
     let mut transcript = format!("Topic: {}\n\n", topic);
     for (i, exchange) in exchanges.iter().enumerate() {
         transcript.push_str(&format!(
@@ -39,20 +155,183 @@ pub async fn judge_debate(
             exchange.opposer.message
         ));
     }
+    transcript
+}
 
-    messages.push(ChatMessage::user(&transcript));
-    messages.push(ChatMessage::user("Who won?"));
-    messages.push(ChatMessage::user(
-        "Respond with only one word 'OPPOSITION' OR 'PROPOSITION'",
-    ));
+// judge view of a summarized debate
+pub fn summary_transcript(topic: &str, summary: &str) -> String {
+    format!(
+        "Topic: {}\n\nSummary of both cases:\n{}\n\n",
+        topic, summary
+    )
+}
 
-    let response = send_message(client, judge_model, &messages).await?;
+// condense each side's case into a few bullet points so long debates are cheaper to judge
+pub async fn summarize_debate(
+    provider: &dyn LlmProvider,
+    model: &str,
+    topic: &str,
+    exchanges: &[Exchange],
+    params: &GenerationParams,
+    retry: &RetryPolicy,
+) -> Result<Completion> {
+    let messages = vec![
+        ChatMessage::system(
+            "Condense each side of this debate into a few bullet points. Keep every argument \
+             that could decide it and add no opinion of your own. Respond with:\n\
+             PROPOSITION:\n- point\n\
+             OPPOSITION:\n- point",
+        ),
+        ChatMessage::user(judge_transcript(topic, exchanges)),
+    ];
+    send_message(provider, model, &messages, params, retry).await
+}
+
+// winner is required, confidence and reason are optional extras
+pub fn parse_verdict(response: &str) -> Result<JudgeVerdict> {
+    // prefer the WINNER: line, fall back to whole response for models that ignore the format
+    let outcome = match field(response, "WINNER:").and_then(parse_winner) {
+        Some(outcome) => outcome,
+        None => match parse_winner(response) {
+            Some(outcome) => outcome,
+            None => anyhow::bail!("Invalid judge response: {}", response),
+        },
+    };
+
+    let confidence = field(response, "CONFIDENCE:")
+        .and_then(|value| value.parse::<f32>().ok())
+        .filter(|value| (0.0..=1.0).contains(value));
+    let rationale = field(response, "REASON:")
+        .filter(|value| !value.is_empty())
+        .map(str::to_string);
+
+    Ok(JudgeVerdict {
+        outcome,
+        rationale,
+        confidence,
+        usage: TokenUsage::default(),
+    })
+}
+
+// side named last wins, "the PROPOSITION was weak, OPPOSITION wins" is an opposer win
+fn parse_winner(text: &str) -> Option<DebateOutcome> {
+    let text = text.to_uppercase();
+    match (text.rfind("PROPOSITION"), text.rfind("OPPOSITION")) {
+        (Some(proposition), Some(opposition)) if opposition > proposition => {
+            Some(DebateOutcome::OpposerWon)
+        }
+        (Some(_), _) => Some(DebateOutcome::ProposerWon),
+        (None, Some(_)) => Some(DebateOutcome::OpposerWon),
+        (None, None) => None,
+    }
+}
+
+// value after a "KEY:" line prefix
+fn field<'a>(response: &'a str, key: &str) -> Option<&'a str> {
+    response
+        .lines()
+        .find_map(|line| line.trim().strip_prefix(key))
+        .map(str::trim)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::block_on;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_retry_recovers_from_transient_errors() {
+        let attempts = AtomicUsize::new(0);
+        let policy = RetryPolicy::new(3, Duration::from_millis(1));
+
+        let result = block_on(retry_with_backoff(&policy, || async {
+            if attempts.fetch_add(1, Ordering::SeqCst) < 2 {
+                Err(TransientError("429 too many requests".to_string()).into())
+            } else {
+                Ok("argument")
+            }
+        }));
+
+        assert_eq!(result.unwrap(), "argument");
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_retry_gives_up() {
+        let attempts = AtomicUsize::new(0);
+        let policy = RetryPolicy::new(2, Duration::from_millis(1));
+
+        let result: Result<()> = block_on(retry_with_backoff(&policy, || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(TransientError("503".to_string()).into())
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_retry_skips_permanent_errors() {
+        let attempts = AtomicUsize::new(0);
+        let policy = RetryPolicy::new(5, Duration::from_millis(1));
+
+        let result: Result<()> = block_on(retry_with_backoff(&policy, || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(anyhow::anyhow!("invalid api key"))
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_parse_structured_verdict() {
+        let verdict = parse_verdict(
+            "WINNER: OPPOSITION\nCONFIDENCE: 0.8\nREASON: Stronger evidence throughout.",
+        )
+        .unwrap();
+
+        assert_eq!(verdict.outcome, DebateOutcome::OpposerWon);
+        assert_eq!(verdict.confidence, Some(0.8));
+        assert_eq!(
+            verdict.rationale.as_deref(),
+            Some("Stronger evidence throughout.")
+        );
+    }
+
+    #[test]
+    fn test_parse_bare_verdict() {
+        let verdict = parse_verdict("PROPOSITION").unwrap();
+
+        assert_eq!(verdict.outcome, DebateOutcome::ProposerWon);
+        assert_eq!(verdict.confidence, None);
+        assert_eq!(verdict.rationale, None);
+    }
+
+    #[test]
+    fn test_parse_verdict_bad_confidence() {
+        let verdict = parse_verdict("WINNER: PROPOSITION\nCONFIDENCE: very").unwrap();
+
+        assert_eq!(verdict.outcome, DebateOutcome::ProposerWon);
+        assert_eq!(verdict.confidence, None);
+    }
+
+    #[test]
+    fn test_parse_verdict_both_keywords() {
+        let verdict = parse_verdict("The PROPOSITION was weak, OPPOSITION wins").unwrap();
+        assert_eq!(verdict.outcome, DebateOutcome::OpposerWon);
+
+        // winner line beats keywords mentioned in the reasoning
+        let verdict = parse_verdict(
+            "WINNER: PROPOSITION\nREASON: The OPPOSITION never answered the PROPOSITION, OPPOSITION lost.",
+        )
+        .unwrap();
+        assert_eq!(verdict.outcome, DebateOutcome::ProposerWon);
+    }
 
-    if response.contains("PROPOSITION") {
-        Ok(DebateOutcome::ProposerWon)
-    } else if response.contains("OPPOSITION") {
-        Ok(DebateOutcome::OpposerWon)
-    } else {
-        anyhow::bail!("Invalid judge response: {}", response)
+    #[test]
+    fn test_parse_verdict_no_winner() {
+        assert!(parse_verdict("It was a tie").is_err());
     }
 }
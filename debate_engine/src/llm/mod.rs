@@ -1,2 +1,4 @@
 mod client;
+mod provider;
 pub use client::*;
+pub use provider::*;
@@ -0,0 +1,91 @@
+use anyhow::Result;
+use core::{GenerationParams, TokenUsage};
+use genai::Client;
+use genai::chat::{ChatMessage, ChatOptions, ChatRequest};
+use std::future::Future;
+use std::pin::Pin;
+
+// boxed so the trait stays object safe and its futures can be spawned
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+// reply text and what it cost
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion {
+    pub text: String,
+    pub usage: TokenUsage,
+}
+
+// anything that can turn a chat history into a reply, genai in production and mocks in tests
+pub trait LlmProvider: Send + Sync {
+    fn complete<'a>(
+        &'a self,
+        model: &'a str,
+        messages: &'a [ChatMessage],
+        params: &'a GenerationParams,
+    ) -> BoxFuture<'a, Result<Completion>>;
+}
+
+// provider backed by a genai client, picks the api from the model name
+#[derive(Debug, Clone, Default)]
+pub struct GenaiProvider {
+    client: Client,
+}
+
+impl GenaiProvider {
+    // reuse an existing client and its connection pool
+    pub fn new(client: Client) -> Self {
+        Self { client }
+    }
+
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+}
+
+impl From<Client> for GenaiProvider {
+    fn from(client: Client) -> Self {
+        Self::new(client)
+    }
+}
+
+impl LlmProvider for GenaiProvider {
+    fn complete<'a>(
+        &'a self,
+        model: &'a str,
+        messages: &'a [ChatMessage],
+        params: &'a GenerationParams,
+    ) -> BoxFuture<'a, Result<Completion>> {
+        Box::pin(async move {
+            let chat_req = ChatRequest::new(messages.to_vec());
+            let options = chat_options(params);
+            let chat_res = self
+                .client
+                .exec_chat(model, chat_req, Some(&options))
+                .await?;
+
+            // providers that dont report usage count as zero
+            let usage = TokenUsage::new(
+                chat_res.usage.prompt_tokens.unwrap_or(0).max(0) as u64,
+                chat_res.usage.completion_tokens.unwrap_or(0).max(0) as u64,
+            );
+            let text = chat_res
+                .first_text()
+                .ok_or_else(|| anyhow::anyhow!("No response from model"))?
+                .to_string();
+
+            Ok(Completion { text, usage })
+        })
+    }
+}
+
+// only the params that are set, the rest fall back to the client defaults
+fn chat_options(params: &GenerationParams) -> ChatOptions {
+    let mut options = ChatOptions::default();
+    if let Some(temperature) = params.temperature {
+        options = options.with_temperature(temperature);
+    }
+    if let Some(max_tokens) = params.max_tokens {
+        options = options.with_max_tokens(max_tokens);
+    }
+    options
+}
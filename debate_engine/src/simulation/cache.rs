@@ -0,0 +1,110 @@
+use anyhow::{Context, Result};
+use core::Debate;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// what a cached debate is looked up by. prompts, personas, format, generation params and who
+// opens are not part of it, change those and the cache has to be cleared by hand
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CacheKey {
+    pub proposer_model: String,
+    pub opposer_model: String,
+    pub topic: String,
+    pub max_turns: usize,
+    pub judge_models: Vec<String>,
+}
+
+// decided debates by their inputs, see Simulation::with_cache. the models are not
+// deterministic, so a hit replays one sample of the debate instead of running a new one.
+// undecided ones are not kept, they are re-run by max_continuations.
+// kept in memory and written back to a json file after every batch that added debates
+pub struct DebateCache {
+    path: PathBuf,
+    debates: Mutex<HashMap<CacheKey, Debate>>,
+}
+
+impl DebateCache {
+    // a missing file starts an empty cache
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let debates = if path.exists() {
+            let json = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read debate cache {}", path.display()))?;
+            let entries: Vec<(CacheKey, Debate)> = serde_json::from_str(&json)
+                .with_context(|| format!("Failed to parse debate cache {}", path.display()))?;
+            entries.into_iter().collect()
+        } else {
+            HashMap::new()
+        };
+
+        Ok(Self {
+            path,
+            debates: Mutex::new(debates),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn len(&self) -> usize {
+        self.debates.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, key: &CacheKey) -> Option<Debate> {
+        self.debates.lock().unwrap().get(key).cloned()
+    }
+
+    // memory only until save
+    pub fn insert(&self, key: CacheKey, debate: Debate) {
+        self.debates.lock().unwrap().insert(key, debate);
+    }
+
+    pub fn save(&self) -> Result<()> {
+        // json objects need string keys, so the file is a list of pairs
+        let entries: Vec<(CacheKey, Debate)> = self
+            .debates
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(key, debate)| (key.clone(), debate.clone()))
+            .collect();
+        let json = serde_json::to_string(&entries).context("Failed to serialize debate cache")?;
+        fs::write(&self.path, json)
+            .with_context(|| format!("Failed to write debate cache {}", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_round_trips_through_its_file() {
+        let path = std::env::temp_dir().join(format!("debate_cache_{}.json", std::process::id()));
+        let key = CacheKey {
+            proposer_model: "a".to_string(),
+            opposer_model: "b".to_string(),
+            topic: "topic".to_string(),
+            max_turns: 1,
+            judge_models: vec!["judge".to_string()],
+        };
+
+        let cache = DebateCache::open(&path).unwrap();
+        assert!(cache.is_empty());
+        cache.insert(key.clone(), Debate::new(0, 1, 1));
+        cache.save().unwrap();
+
+        let reopened = DebateCache::open(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(reopened.len(), 1);
+        assert_eq!(reopened.get(&key), Some(Debate::new(0, 1, 1)));
+    }
+}
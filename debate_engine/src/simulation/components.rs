@@ -0,0 +1,245 @@
+use crate::simulation::engine::{Budget, snapshot};
+use crate::{Simulation, SimulationResult, TerminationReason};
+use anyhow::Result;
+use core::{InfectionStatus, Registry, RegistryError, RegistryStatistics, TokenUsage};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinSet;
+
+impl Simulation {
+    // like run, but every connected component of the topology runs as its own task on its own
+    // part of the registry, so a slow component never holds up the batches of another. parts
+    // are merged back when all are done. max_rounds and max_api_calls cap the whole run, every
+    // component draws from one budget. components without an infected agent are not run at all
+    pub async fn run_components(&self, registry: &mut Registry) -> Result<SimulationResult> {
+        registry.validate_topology()?;
+        if registry.infected_count() == 0 {
+            return Err(RegistryError::NoInfectedAgents.into());
+        }
+        let started = Instant::now();
+
+        let parts = registry.split_components();
+        let mut idle_stats = Vec::new();
+        let mut idle_snapshot = HashMap::new();
+        let budget = Arc::new(Budget::new(self));
+        let mut tasks = JoinSet::new();
+        for (index, mut part) in parts.into_iter().enumerate() {
+            if part.infected_count() == 0 {
+                idle_stats.push(part.get_statistics());
+                if self.record_snapshots {
+                    idle_snapshot.extend(snapshot(&part));
+                }
+                continue;
+            }
+            let sim = self.clone();
+            let budget = Arc::clone(&budget);
+            tasks.spawn(async move {
+                let result = sim.run_loop(&mut part, sim.rng(), None, budget).await;
+                (index, part, result)
+            });
+        }
+
+        // tasks finish in any order, keep component order so runs are reproducible
+        let mut finished = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            let (index, part, result) = joined?;
+            finished.push((index, part, result?));
+        }
+        finished.sort_unstable_by_key(|(index, _, _)| *index);
+
+        let mut results = Vec::new();
+        for (_, part, result) in finished {
+            registry.absorb(part);
+            results.push(result);
+        }
+
+        Ok(self.merge_results(
+            registry,
+            results,
+            idle_stats,
+            idle_snapshot,
+            started.elapsed(),
+        ))
+    }
+
+    fn merge_results(
+        &self,
+        registry: &Registry,
+        results: Vec<SimulationResult>,
+        idle_stats: Vec<RegistryStatistics>,
+        idle_snapshot: HashMap<u32, InfectionStatus>,
+        wall_time: Duration,
+    ) -> SimulationResult {
+        let stats = registry.get_statistics();
+
+        // components that stopped early keep counting with their last stats
+        let steps = results.iter().map(|result| result.timeline.len()).max();
+        let timeline = (0..steps.unwrap_or(0))
+            .map(|step| {
+                let mut total = RegistryStatistics {
+                    total_connections: stats.total_connections,
+                    ..RegistryStatistics::default()
+                };
+                let mut add = |part: &RegistryStatistics| {
+                    total.total_agents += part.total_agents;
+                    total.infected_agents += part.infected_agents;
+                    total.healthy_agents += part.healthy_agents;
+                    total.immune_agents += part.immune_agents;
+                };
+                for result in &results {
+                    if let Some(part) = result.timeline.get(step).or(result.timeline.last()) {
+                        add(part);
+                    }
+                }
+                idle_stats.iter().for_each(&mut add);
+                total
+            })
+            .collect();
+
+        // same for snapshots, idle components never change
+        let frames = results.iter().map(|result| result.snapshots.len()).max();
+        let snapshots = (0..frames.unwrap_or(0))
+            .map(|frame| {
+                let mut total = idle_snapshot.clone();
+                for result in &results {
+                    if let Some(part) = result.snapshots.get(frame).or(result.snapshots.last()) {
+                        total.extend(part);
+                    }
+                }
+                total
+            })
+            .collect();
+
+        // a budget or a cancel in any component explains the run better than the frontier did
+        let terminated_reason = [
+            TerminationReason::Cancelled,
+            TerminationReason::MaxApiCalls,
+            TerminationReason::MaxRounds,
+        ]
+        .into_iter()
+        .find(|reason| {
+            results
+                .iter()
+                .any(|result| result.terminated_reason == *reason)
+        })
+        .unwrap_or(if stats.healthy_agents == 0 {
+            TerminationReason::Saturated
+        } else {
+            TerminationReason::NoReachableTargets
+        });
+
+        let mut debates = Vec::new();
+        let mut timed_out = Vec::new();
+        let mut patient_zeros = Vec::new();
+        let mut transmissions: HashMap<u32, Vec<u32>> = HashMap::new();
+        let mut usage = TokenUsage::default();
+        let mut api_calls = 0;
+        for result in results {
+            debates.extend(result.debates);
+            timed_out.extend(result.timed_out);
+            patient_zeros.extend(result.patient_zeros);
+            transmissions.extend(result.transmissions);
+            usage += result.usage;
+            api_calls += result.api_calls;
+        }
+        timed_out.sort_unstable();
+        patient_zeros.sort_unstable();
+
+        SimulationResult {
+            topic: self.topic.clone(),
+            rounds: debates.len(),
+            total_agents: stats.total_agents,
+            infected: stats.infected_agents,
+            healthy: stats.healthy_agents,
+            immune: stats.immune_agents,
+            debates,
+            timed_out,
+            timeline,
+            snapshots,
+            patient_zeros,
+            transmissions,
+            usage,
+            api_calls,
+            terminated_reason,
+            wall_time,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockProvider, block_on};
+    use core::{InfectionStatus, TopologyBuilder};
+
+    #[test]
+    fn test_run_components_merges_disjoint_stars() {
+        let mut registry = Registry::new();
+        for _ in 0..9 {
+            registry.create_agent("model".to_string());
+        }
+        let mut topology = TopologyBuilder::star(0, &[1, 2, 3]);
+        for spoke in [5, 6] {
+            topology.add_connection(4, spoke);
+        }
+        registry.topology = Some(topology);
+        registry.infect_patients_init(&[0, 4]).unwrap();
+
+        let sim = Simulation::new("topic", 1, "judge")
+            .with_provider(MockProvider::judge_always("PROPOSITION"));
+        let result = block_on(sim.run_components(&mut registry)).unwrap();
+
+        assert_eq!(result.rounds, 5);
+        assert_eq!(result.patient_zeros, vec![0, 4]);
+        assert_eq!(result.transmissions[&0], vec![1, 2, 3]);
+        assert_eq!(result.transmissions[&4], vec![5, 6]);
+        assert_eq!(result.infected, 7);
+        // 7 and 8 are not in the topology and stay healthy
+        assert_eq!(result.healthy, 2);
+        assert_eq!(
+            result.terminated_reason,
+            TerminationReason::NoReachableTargets
+        );
+        assert_eq!(registry.get_agent(6).unwrap().infected_by, Some(4));
+        assert_eq!(
+            registry.get_agent(8).unwrap().infection_status,
+            InfectionStatus::Healthy
+        );
+        assert_eq!(result.timeline.last().unwrap().infected_agents, 7);
+        assert_eq!(result.timeline.last().unwrap().total_agents, 9);
+    }
+
+    #[test]
+    fn test_run_components_share_the_budgets() {
+        // two stars of 3 spokes, either one alone fits in each cap
+        let stars = || {
+            let mut registry = Registry::new();
+            for _ in 0..8 {
+                registry.create_agent("model".to_string());
+            }
+            let mut topology = TopologyBuilder::star(0, &[1, 2, 3]);
+            topology.merge(&TopologyBuilder::star(4, &[5, 6, 7]));
+            registry.topology = Some(topology);
+            registry.infect_patients_init(&[0, 4]).unwrap();
+            registry
+        };
+        let sim = Simulation::new("topic", 1, "judge")
+            .with_parallelism(1)
+            .with_provider(MockProvider::judge_always("PROPOSITION"));
+
+        let mut registry = stars();
+        let result =
+            block_on(sim.clone().with_max_rounds(4).run_components(&mut registry)).unwrap();
+        assert_eq!(result.rounds, 4);
+        assert_eq!(result.infected, 6);
+        assert_eq!(result.terminated_reason, TerminationReason::MaxRounds);
+
+        // 3 calls per debate, so 9 calls are 3 debates across both components
+        let mut registry = stars();
+        let result = block_on(sim.with_max_api_calls(9).run_components(&mut registry)).unwrap();
+        assert_eq!(result.rounds, 3);
+        assert_eq!(result.api_calls, 9);
+        assert_eq!(result.terminated_reason, TerminationReason::MaxApiCalls);
+    }
+}
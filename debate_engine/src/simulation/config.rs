@@ -0,0 +1,238 @@
+use crate::Simulation;
+use anyhow::{Context, Result};
+use core::{Registry, RegistryBuilder, Topology, TopologyBuilder};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+// a whole experiment in one toml file, e.g.
+//
+//   topic = "Does pineapple belong on pizza"
+//   turns = 2
+//   judge = "gpt-5.2-chat-latest"
+//   parallelism = 5
+//   seed = 42
+//   patient_zeros = [2]
+//
+//   [topology]
+//   kind = "star"
+//   center = 2
+//
+//   [[agents]]
+//   model = "gpt-3.5-turbo"
+//
+// agents get ids in file order starting at 0
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimulationConfig {
+    pub topic: String,
+    pub turns: usize,
+    pub judge: String,
+    pub parallelism: Option<usize>,
+    // seeds the simulation and any random topology
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub patient_zeros: Vec<u32>,
+    pub topology: TopologyConfig,
+    pub agents: Vec<AgentConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AgentConfig {
+    pub model: String,
+}
+
+// one TopologyBuilder call over every agent, kind picks which
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TopologyConfig {
+    FullyConnected,
+    Ring,
+    // every other agent is a spoke
+    Star { center: u32 },
+    Random { probability: f64 },
+    SmallWorld { k: usize, beta: f64 },
+    ScaleFree { m: usize },
+    Grid { rows: usize, cols: usize },
+    Torus { rows: usize, cols: usize },
+    // explicit undirected connections
+    Edges { edges: Vec<(u32, u32)> },
+}
+
+impl TopologyConfig {
+    pub fn build(&self, agent_ids: &[u32], seed: Option<u64>) -> Result<Topology> {
+        let topology = match *self {
+            TopologyConfig::FullyConnected => TopologyBuilder::fully_connected(agent_ids),
+            TopologyConfig::Ring => TopologyBuilder::ring(agent_ids),
+            TopologyConfig::Star { center } => {
+                let periphery: Vec<u32> = agent_ids
+                    .iter()
+                    .copied()
+                    .filter(|&id| id != center)
+                    .collect();
+                TopologyBuilder::star(center, &periphery)
+            }
+            TopologyConfig::Random { probability } => {
+                if !(0.0..=1.0).contains(&probability) {
+                    anyhow::bail!(
+                        "probability must be between 0.0 and 1.0, got {}",
+                        probability
+                    );
+                }
+                match seed {
+                    Some(seed) => TopologyBuilder::random_seeded(agent_ids, probability, seed),
+                    None => TopologyBuilder::random(agent_ids, probability),
+                }
+            }
+            TopologyConfig::SmallWorld { k, beta } => match seed {
+                Some(seed) => TopologyBuilder::try_small_world_seeded(agent_ids, k, beta, seed)?,
+                None => TopologyBuilder::try_small_world(agent_ids, k, beta)?,
+            },
+            TopologyConfig::ScaleFree { m } => match seed {
+                Some(seed) => TopologyBuilder::try_scale_free_seeded(agent_ids, m, seed)?,
+                None => TopologyBuilder::try_scale_free(agent_ids, m)?,
+            },
+            TopologyConfig::Grid { rows, cols } => {
+                TopologyBuilder::try_grid(rows, cols, agent_ids)?
+            }
+            TopologyConfig::Torus { rows, cols } => {
+                TopologyBuilder::try_torus(rows, cols, agent_ids)?
+            }
+            TopologyConfig::Edges { ref edges } => Topology::from_edges(edges),
+        };
+        Ok(topology)
+    }
+}
+
+impl SimulationConfig {
+    pub fn from_toml_file(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config {}", path.display()))?;
+        Self::from_toml_str(&text).with_context(|| format!("Invalid config {}", path.display()))
+    }
+
+    pub fn from_toml_str(text: &str) -> Result<Self> {
+        Ok(toml::from_str(text)?)
+    }
+
+    // fails if the topology or a patient zero names an agent the config doesnt list
+    pub fn build(&self) -> Result<(Registry, Simulation)> {
+        let agent_ids: Vec<u32> = (0..self.agents.len() as u32).collect();
+        let models: Vec<&str> = self
+            .agents
+            .iter()
+            .map(|agent| agent.model.as_str())
+            .collect();
+
+        let mut builder = RegistryBuilder::new()
+            .agents(&models)
+            .topology(self.topology.build(&agent_ids, self.seed)?);
+        for &agent_id in &self.patient_zeros {
+            builder = builder.patient_zero(agent_id);
+        }
+        let registry = builder.build()?;
+
+        let mut sim = Simulation::new(&self.topic, self.turns, &self.judge);
+        if let Some(parallelism) = self.parallelism {
+            sim = sim.with_parallelism(parallelism);
+        }
+        if let Some(seed) = self.seed {
+            sim = sim.with_seed(seed);
+        }
+
+        Ok((registry, sim))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::RegistryError;
+
+    const SAMPLE: &str = r#"
+        topic = "Does pineapple belong on pizza"
+        turns = 3
+        judge = "gpt-4"
+        parallelism = 2
+        seed = 7
+        patient_zeros = [2]
+
+        [topology]
+        kind = "star"
+        center = 2
+
+        [[agents]]
+        model = "gpt-3.5-turbo"
+
+        [[agents]]
+        model = "gpt-3.5-turbo"
+
+        [[agents]]
+        model = "gpt-4"
+
+        [[agents]]
+        model = "llama3"
+    "#;
+
+    #[test]
+    fn test_sample_config_builds_registry_and_simulation() {
+        let config = SimulationConfig::from_toml_str(SAMPLE).unwrap();
+
+        let (registry, sim) = config.build().unwrap();
+
+        assert_eq!(registry.agent_count(), 4);
+        assert_eq!(registry.get_agent(2).unwrap().model, "gpt-4");
+        assert_eq!(registry.get_infected_agent_ids(), vec![2]);
+        assert_eq!(
+            registry.topology,
+            Some(TopologyBuilder::star(2, &[0, 1, 3]))
+        );
+        assert_eq!(sim.max_turns, 3);
+        assert_eq!(sim.judge_models, vec!["gpt-4".to_string()]);
+        assert_eq!(sim.max_parallel_debates, 2);
+        assert_eq!(sim.seed, Some(7));
+    }
+
+    #[test]
+    fn test_seeded_topologies_are_reproducible() {
+        let agent_ids: Vec<u32> = (0..30).collect();
+        for topology in [
+            TopologyConfig::Random { probability: 0.2 },
+            TopologyConfig::SmallWorld { k: 4, beta: 0.5 },
+            TopologyConfig::ScaleFree { m: 2 },
+        ] {
+            assert_eq!(
+                topology.build(&agent_ids, Some(42)).unwrap(),
+                topology.build(&agent_ids, Some(42)).unwrap(),
+                "{:?}",
+                topology
+            );
+        }
+    }
+
+    #[test]
+    fn test_config_errors() {
+        // a star center that isnt one of the agents
+        let config =
+            SimulationConfig::from_toml_str(&SAMPLE.replace("center = 2", "center = 9")).unwrap();
+        let Err(err) = config.build() else {
+            panic!("unknown star center was accepted");
+        };
+        assert_eq!(
+            err.downcast_ref::<RegistryError>(),
+            Some(&RegistryError::UnknownTopologyAgents(vec![9]))
+        );
+
+        assert!(
+            SimulationConfig::from_toml_str(&SAMPLE.replace("\"star\"", "\"hexagon\"")).is_err()
+        );
+        let edges = SAMPLE.replace(
+            "kind = \"star\"\n        center = 2",
+            "kind = \"edges\"\n        edges = [[0, 1], [1, 3]]",
+        );
+        let (registry, _) = SimulationConfig::from_toml_str(&edges)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(registry.topology.unwrap().connection_count(), 2);
+    }
+}
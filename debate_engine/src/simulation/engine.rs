@@ -1,15 +1,102 @@
-use crate::run_debate;
-use anyhow::Result;
-use core::{Debate, DebateOutcome, Registry};
-use std::collections::{HashSet, VecDeque};
+use crate::{
+    BoxFuture, CacheKey, Completion, DebateCache, DebateConfig, DebateFormat, GenaiProvider,
+    LlmProvider, NoProgress, ProgressReporter, PromptTemplates, RetryPolicy, SimulationEvent,
+    run_debate,
+};
+use anyhow::{Context, Result};
+use core::{
+    Agent, Debate, DebateOutcome, GenerationParams, InfectionStatus, Registry, RegistryError,
+    RegistryStatistics, TokenUsage,
+};
+use genai::Client;
+use genai::chat::ChatMessage;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::UnboundedSender;
 use tokio::task::JoinSet;
+use tracing::Instrument;
 
 /// High-level simulation orchestrator
+#[derive(Clone)]
 pub struct Simulation {
     pub topic: String,
     pub max_turns: usize,
-    pub judge_model: String,
+    // several judges vote by majority, see with_judges
+    pub judge_models: Vec<String>,
+    // sampling settings for the judges, debaters use their agent's gen_params
+    pub judge_params: GenerationParams,
     pub max_parallel_debates: usize,
+    // seeds any randomised choice made during a run, none draws from entropy
+    pub seed: Option<u64>,
+    // debates running longer than this are skipped instead of stalling the run
+    pub debate_timeout: Option<Duration>,
+    // backoff for transient provider errors (429s, 5xx)
+    pub retry: RetryPolicy,
+    pub prompts: PromptTemplates,
+    // free form by default, see DebateFormat
+    pub format: DebateFormat,
+    // chance an opposer who won its debate is infected anyway, 0.0 keeps the judge's verdict
+    pub infection_probability: f64,
+    // sirs mode, infected and immune agents turn healthy again after this many batches.
+    // recovered agents leave the frontier and can be re-targeted, but only over edges not yet
+    // debated, every edge is still debated at most once so the run always terminates
+    pub recovery_after: Option<usize>,
+    // hard budget on completed debates, the run stops with MaxRounds once it is reached
+    pub max_rounds: Option<usize>,
+    // budget on provider calls, a debate is only scheduled if its 2 * max_turns + judges calls
+    // (one more when summarizing) still fit. retried requests count as calls too, so with
+    // retries enabled a flaky provider can push the total past the cap
+    pub max_api_calls: Option<usize>,
+    // immune agents can be debated again and are infected if they lose, each edge is still
+    // only debated once. off keeps immunity permanent
+    pub can_reinfect_immune: bool,
+    // a debate the judges leave undecided (a tied vote, DebateOutcome::Ongoing) is run again
+    // from scratch on the same edge up to this many times. 0 keeps undecided debates final,
+    // the opposer just stays healthy. every edge is still debated at most
+    // 1 + max_continuations times, so the run always terminates
+    pub max_continuations: usize,
+    // chance the proposer wins a run_dry debate
+    pub dry_run_bias: f64,
+    // per model strength, roughly 0.0 - 1.0, for run_dry. when set the proposer wins with
+    // chance 0.5 + (proposer - opposer) / 2, i.e. its strength plus uniform noise beats the
+    // opposer's, and dry_run_bias is ignored. models missing from the map count as 0.0
+    pub model_strengths: HashMap<String, f64>,
+    // pick the side that opens each debate with the seeded rng instead of always the
+    // proposer, removing the first mover advantage. infection roles stay as they are
+    pub randomize_opening: bool,
+    // shuffle the candidate edges with the seeded rng before a batch is cut to
+    // max_parallel_debates. off fills batches in id order, which favours low ids whenever
+    // there are more candidates than slots
+    pub shuffle_batches: bool,
+    // debater replies are cut to this many characters, see DebateConfig
+    pub max_response_chars: Option<usize>,
+    // ask a model to condense both cases into bullet points and judge that instead of the
+    // exchanges, cheaper for long debates. costs one extra call per debate
+    pub summarize_before_judge: bool,
+    // model writing the summaries, none uses the first judge
+    pub summarizer_model: Option<String>,
+    // keep every agent's status after each batch in SimulationResult::snapshots. off by
+    // default, that is one map of all agents per batch
+    pub record_snapshots: bool,
+    // progress events as debates start and finish, a dropped receiver is ignored
+    pub events: Option<UnboundedSender<SimulationEvent>>,
+    // set to true from anywhere to stop the run, debates already in flight finish and the
+    // partial result is returned
+    pub cancel: Option<Arc<AtomicBool>>,
+    // told after every batch how far the run got, see ProgressReporter
+    pub progress: Arc<dyn ProgressReporter>,
+    // replays debates whose inputs were seen before instead of calling the models, see
+    // DebateCache
+    pub cache: Option<Arc<DebateCache>>,
+    // built once and shared by every debate so the client reuses its connection pool
+    pub provider: Arc<dyn LlmProvider>,
 }
 
 impl Simulation {
@@ -17,8 +104,33 @@ impl Simulation {
         Self {
             topic: topic.into(),
             max_turns,
-            judge_model: judge_model.into(),
+            judge_models: vec![judge_model.into()],
+            judge_params: GenerationParams::default(),
             max_parallel_debates: 4,
+            seed: None,
+            debate_timeout: None,
+            retry: RetryPolicy::default(),
+            prompts: PromptTemplates::default(),
+            format: DebateFormat::default(),
+            infection_probability: 0.0,
+            recovery_after: None,
+            max_rounds: None,
+            max_api_calls: None,
+            can_reinfect_immune: false,
+            max_continuations: 0,
+            dry_run_bias: 0.5,
+            model_strengths: HashMap::new(),
+            randomize_opening: false,
+            shuffle_batches: false,
+            max_response_chars: None,
+            summarize_before_judge: false,
+            summarizer_model: None,
+            record_snapshots: false,
+            events: None,
+            cancel: None,
+            progress: Arc::new(NoProgress),
+            cache: None,
+            provider: Arc::new(GenaiProvider::default()),
         }
     }
 
@@ -27,32 +139,412 @@ impl Simulation {
         self
     }
 
+    // replaces the judge passed to new
+    pub fn with_judges(
+        mut self,
+        judge_models: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.judge_models = judge_models.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_judge_params(mut self, judge_params: GenerationParams) -> Self {
+        self.judge_params = judge_params;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn with_debate_timeout(mut self, timeout: Duration) -> Self {
+        self.debate_timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    // customise debater and judge prompts, see PromptTemplates
+    pub fn with_prompts(mut self, prompts: PromptTemplates) -> Self {
+        self.prompts = prompts;
+        self
+    }
+
+    pub fn with_format(mut self, format: DebateFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    // models imperfect persuasion, clamped to 0.0 - 1.0
+    pub fn with_infection_probability(mut self, probability: f64) -> Self {
+        self.infection_probability = probability.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_recovery_after(mut self, rounds: usize) -> Self {
+        self.recovery_after = Some(rounds);
+        self
+    }
+
+    pub fn with_max_rounds(mut self, max_rounds: usize) -> Self {
+        self.max_rounds = Some(max_rounds);
+        self
+    }
+
+    pub fn with_max_api_calls(mut self, max_api_calls: usize) -> Self {
+        self.max_api_calls = Some(max_api_calls);
+        self
+    }
+
+    // clamped to 0.0 - 1.0
+    pub fn with_dry_run_bias(mut self, bias: f64) -> Self {
+        self.dry_run_bias = bias.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_model_strengths(mut self, model_strengths: HashMap<String, f64>) -> Self {
+        self.model_strengths = model_strengths;
+        self
+    }
+
+    pub fn with_max_continuations(mut self, max_continuations: usize) -> Self {
+        self.max_continuations = max_continuations;
+        self
+    }
+
+    pub fn with_reinfection(mut self, can_reinfect_immune: bool) -> Self {
+        self.can_reinfect_immune = can_reinfect_immune;
+        self
+    }
+
+    pub fn with_randomized_opening(mut self, randomize_opening: bool) -> Self {
+        self.randomize_opening = randomize_opening;
+        self
+    }
+
+    pub fn with_shuffled_batches(mut self, shuffle_batches: bool) -> Self {
+        self.shuffle_batches = shuffle_batches;
+        self
+    }
+
+    pub fn with_max_response_chars(mut self, max_chars: usize) -> Self {
+        self.max_response_chars = Some(max_chars);
+        self
+    }
+
+    pub fn with_summarize_before_judge(mut self, summarize_before_judge: bool) -> Self {
+        self.summarize_before_judge = summarize_before_judge;
+        self
+    }
+
+    pub fn with_summarizer_model(mut self, summarizer_model: impl Into<String>) -> Self {
+        self.summarizer_model = Some(summarizer_model.into());
+        self
+    }
+
+    pub fn with_snapshots(mut self, record_snapshots: bool) -> Self {
+        self.record_snapshots = record_snapshots;
+        self
+    }
+
+    // e.g. to drive a progress bar, see SimulationEvent
+    pub fn with_events(mut self, events: UnboundedSender<SimulationEvent>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    pub fn with_cancellation(mut self, cancel: Arc<AtomicBool>) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    pub fn with_progress(mut self, progress: impl ProgressReporter + 'static) -> Self {
+        self.progress = Arc::new(progress);
+        self
+    }
+
+    // opt in debate cache for development runs. a hit hands back the stored debate, so reruns
+    // show one sampled answer per pairing rather than fresh model output and spend no calls.
+    // loads the file if it exists, it is rewritten after every batch that ran new debates
+    pub fn with_cache(mut self, path: impl Into<PathBuf>) -> Result<Self> {
+        self.cache = Some(Arc::new(DebateCache::open(path)?));
+        Ok(self)
+    }
+
+    // talk to the models through a configured genai client, e.g. one whose service target
+    // resolver points at a local ollama server. replaces any provider set before
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.provider = Arc::new(GenaiProvider::new(client));
+        self
+    }
+
+    // swap the llm backend, e.g. a mock for tests
+    pub fn with_provider(mut self, provider: impl LlmProvider + 'static) -> Self {
+        self.provider = Arc::new(provider);
+        self
+    }
+
+    fn emit(&self, event: SimulationEvent) {
+        if let Some(events) = &self.events {
+            let _ = events.send(event);
+        }
+    }
+
+    pub(super) fn cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(|cancel| cancel.load(Ordering::SeqCst))
+    }
+
+    // agents a proposer may debate under the configured reinfection rule
+    fn targets(&self, registry: &Registry, proposer_id: u32) -> Vec<u32> {
+        if self.can_reinfect_immune {
+            registry.get_reinfection_targets(proposer_id)
+        } else {
+            registry.get_potential_targets(proposer_id)
+        }
+    }
+
+    // debates done so far plus the edges the frontier could still debate
+    fn report_progress(&self, registry: &Registry, state: &RunState) {
+        let done = state.debates.len() + state.timed_out.len();
+        let pending: usize = state
+            .infected_deque
+            .iter()
+            .map(|&id| {
+                self.targets(registry, id)
+                    .into_iter()
+                    .filter(|&target| !state.visited_edges.contains(&(id, target)))
+                    .count()
+            })
+            .sum();
+        let mut total_estimate = done + pending;
+        if let Some(max_rounds) = self.max_rounds {
+            total_estimate = total_estimate.min(max_rounds.max(done));
+        }
+        self.progress.on_progress(done, total_estimate);
+    }
+
+    // requests a debate makes without retries
+    fn calls_per_debate(&self) -> usize {
+        2 * self.max_turns + self.judge_models.len() + usize::from(self.summarize_before_judge)
+    }
+
+    fn cache_key(&self, proposer_model: &str, opposer_model: &str) -> CacheKey {
+        CacheKey {
+            proposer_model: proposer_model.to_string(),
+            opposer_model: opposer_model.to_string(),
+            topic: self.topic.clone(),
+            max_turns: self.max_turns,
+            judge_models: self.judge_models.clone(),
+        }
+    }
+
+    // per debate settings handed to run_debate
+    fn debate_config(&self) -> DebateConfig {
+        DebateConfig {
+            topic: self.topic.clone(),
+            max_turns: self.max_turns,
+            judge_models: self.judge_models.clone(),
+            judge_params: self.judge_params,
+            retry: self.retry,
+            prompts: self.prompts.clone(),
+            opposer_opens: false,
+            format: self.format,
+            max_response_chars: self.max_response_chars,
+            summarizer_model: match self.summarize_before_judge {
+                true => self
+                    .summarizer_model
+                    .clone()
+                    .or_else(|| self.judge_models.first().cloned()),
+                false => None,
+            },
+        }
+    }
+
+    // seeded when a seed is set, otherwise from entropy
+    pub(super) fn rng(&self) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        }
+    }
+
     // sim loop
     pub async fn run(&self, registry: &mut Registry) -> Result<SimulationResult> {
-        // get all infected agents in the registry
-        let mut infected_deque: VecDeque<u32> = registry.get_infected_agent_ids().into();
+        let budget = Arc::new(Budget::new(self));
+        self.run_loop(registry, self.rng(), None, budget).await
+    }
+
+    // same spread without any llm calls, each debate is a seeded coin flip the proposer wins
+    // with probability dry_run_bias, or by model strength, see model_strengths.
+    // debates come back without exchanges, everything else (frontier, budgets, recovery, events)
+    // behaves like run
+    pub async fn run_dry(
+        &self,
+        registry: &mut Registry,
+        rng_seed: u64,
+    ) -> Result<SimulationResult> {
+        let rng = StdRng::seed_from_u64(rng_seed);
+        let budget = Arc::new(Budget::new(self));
+        self.run_loop(registry, rng, Some(self.dry_run_bias), budget)
+            .await
+    }
 
-        let mut all_debates = Vec::new();
+    // dry_bias replaces real debates with coin flips, see run_dry. the budget may be shared
+    // with other runs, see run_components
+    pub(super) async fn run_loop(
+        &self,
+        registry: &mut Registry,
+        rng: StdRng,
+        dry_bias: Option<f64>,
+        budget: Arc<Budget>,
+    ) -> Result<SimulationResult> {
+        // a typo'd id in the topology would otherwise just never be reached
+        registry.validate_topology()?;
+
+        // get all infected agents in the registry, sorted so batches are reproducible
+        let infected_ids = registry.get_infected_agent_ids();
+        // an empty frontier would just come back as an all zero result
+        if infected_ids.is_empty() {
+            return Err(RegistryError::NoInfectedAgents.into());
+        }
+        tracing::info!(
+            topic = %self.topic,
+            agents = registry.agent_count(),
+            patient_zeros = ?infected_ids,
+            "starting simulation"
+        );
+        let mut state = RunState::new(infected_ids.clone(), rng);
+        let mut terminated_reason = None;
 
         // loop suntil
-        while !infected_deque.is_empty() {
+        while !state.infected_deque.is_empty() {
+            if self.cancelled() {
+                terminated_reason = Some(TerminationReason::Cancelled);
+                break;
+            }
+
+            if let Some(reason) = budget.exhausted() {
+                terminated_reason = Some(reason);
+                break;
+            }
+
             // create a batch of infected--healthy edges, len constrainted by max_parallel
-            let batch = self.build_debate_batch(registry, &infected_deque);
+            let mut batch = self.build_debate_batch(
+                registry,
+                &state.infected_deque,
+                &state.visited_edges,
+                &mut state.rng,
+            );
 
-            // if batch is empty, check for every id they still have targets else remove
+            // if batch is empty, check for every id they still have unvisited targets else remove
             if batch.is_empty() {
-                infected_deque.retain(|&id| !registry.get_potential_targets(id).is_empty());
+                let visited_edges = &state.visited_edges;
+                state.infected_deque.retain(|&id| {
+                    self.targets(registry, id)
+                        .iter()
+                        .any(|&target| !visited_edges.contains(&(id, target)))
+                });
                 continue;
             }
+            // a batch never overshoots the budgets, another component may have spent them since
+            let reserved = match budget.reserve(batch.len()) {
+                Ok(reserved) => reserved,
+                Err(reason) => {
+                    terminated_reason = Some(reason);
+                    break;
+                }
+            };
+            batch.truncate(reserved);
+            state.visited_edges.extend(batch.iter().copied());
+            tracing::debug!(?batch, "running batch");
 
             // run the batch async
-            let debates = self.run_debate_batch(registry, &batch).await?;
+            let (debates, timed_out) = match dry_bias {
+                Some(bias) => (
+                    self.dry_debate_batch(registry, &batch, bias, &mut state.rng),
+                    Vec::new(),
+                ),
+                None => {
+                    self.run_debate_batch(
+                        registry,
+                        &batch,
+                        &state.api_calls,
+                        &budget,
+                        &mut state.rng,
+                    )
+                    .await?
+                }
+            };
+            budget.release(reserved, debates.len());
+            state.timed_out.extend(timed_out);
+
+            self.apply_batch_results(registry, debates, &mut state)?;
 
-            self.apply_batch_results(registry, debates, &mut infected_deque, &mut all_debates)?;
+            // recovered agents are healthy again and can no longer infect anyone
+            if let Some(recovery_after) = self.recovery_after {
+                let recovered = registry.recover_agents(recovery_after);
+                state.infected_deque.retain(|id| !recovered.contains(id));
+            }
+            state.timeline.push(registry.get_statistics());
+            if self.record_snapshots {
+                state.snapshots.push(snapshot(registry));
+            }
+            self.report_progress(registry, &state);
         }
 
+        // otherwise the frontier ran dry, either nobody is left to infect or the rest cant be reached
+        let terminated_reason = terminated_reason.unwrap_or(if registry.healthy_count() == 0 {
+            TerminationReason::Saturated
+        } else {
+            TerminationReason::NoReachableTargets
+        });
+
+        tracing::info!(
+            reason = ?terminated_reason,
+            debates = state.debates.len(),
+            "simulation finished"
+        );
+
         // finalize results
-        Ok(self.finalize(registry, all_debates))
+        Ok(self.finalize(registry, state, terminated_reason))
+    }
+
+    // one run per topic in order, each from the same starting point: before every topic the
+    // registry is reset (see reset_infections), then the agents infected and vaccinated when
+    // this was called are infected and vaccinated again. debate histories do not carry over
+    pub async fn run_topics(
+        &self,
+        registry: &mut Registry,
+        topics: &[String],
+    ) -> Result<Vec<SimulationResult>> {
+        let patient_zeros = registry.get_infected_agent_ids();
+        let vaccinated: Vec<u32> = registry
+            .get_all_agents()
+            .into_iter()
+            .filter(|agent| agent.vaccinated)
+            .map(|agent| agent.id)
+            .collect();
+
+        let mut results = Vec::new();
+        for topic in topics {
+            registry.reset_infections();
+            registry.vaccinate(&vaccinated)?;
+            registry.infect_patients_init(&patient_zeros)?;
+
+            let sim = Simulation {
+                topic: topic.clone(),
+                ..self.clone()
+            };
+            results.push(sim.run(registry).await?);
+        }
+
+        Ok(results)
     }
 
     // create batch of debates to be ran async
@@ -60,133 +552,558 @@ impl Simulation {
         &self,
         registry: &Registry,
         infected_deque: &VecDeque<u32>,
+        visited_edges: &HashSet<(u32, u32)>,
+        rng: &mut StdRng,
     ) -> Vec<(u32, u32)> {
+        // every infected--target edge not debated in an earlier batch, in deque then id order
+        let mut candidates: Vec<(u32, u32)> = infected_deque
+            .iter()
+            .flat_map(|&proposer_id| {
+                self.targets(registry, proposer_id)
+                    .into_iter()
+                    .map(move |opposer_id| (proposer_id, opposer_id))
+            })
+            .filter(|edge| !visited_edges.contains(edge))
+            .collect();
+        if self.shuffle_batches {
+            candidates.shuffle(rng);
+        }
+
         let mut batch = Vec::new();
         let mut used_opposers = HashSet::new();
+        for (proposer_id, opposer_id) in candidates {
+            // an opposer debates only one proposer per batch
+            if !used_opposers.insert(opposer_id) {
+                continue;
+            }
+            batch.push((proposer_id, opposer_id));
 
-        // for infected agents in infected_deque
-        for &proposer_id in infected_deque {
-            // for targets connected to infected agent
-            for opposer_id in registry.get_potential_targets(proposer_id) {
-                // create edge for each target
-                let edge = (proposer_id, opposer_id);
-
-                // skip if already used previously
-                if used_opposers.contains(&opposer_id) {
-                    continue;
-                }
-
-                // add to current batch, current used list
-                batch.push(edge);
-                used_opposers.insert(opposer_id);
-
-                // if reaches constraint return batch
-                if batch.len() >= self.max_parallel_debates {
-                    return batch;
-                }
+            // if reaches constraint return batch
+            if batch.len() >= self.max_parallel_debates {
+                break;
             }
         }
 
         batch
     }
 
-    async fn run_debate_batch(
+    pub(super) async fn run_debate_batch(
         &self,
         registry: &Registry,
         pairs: &[(u32, u32)],
-    ) -> Result<Vec<Debate>> {
+        api_calls: &Arc<AtomicUsize>,
+        budget: &Arc<Budget>,
+        rng: &mut StdRng,
+    ) -> Result<(Vec<Debate>, Vec<(u32, u32)>)> {
         // container for async tasks
         let mut tasks = JoinSet::new();
+        let config = Arc::new(self.debate_config());
+        let flipped = Arc::new(self.debate_config().with_opposer_opening(true));
+        let counted: Arc<dyn LlmProvider> = Arc::new(CountingProvider {
+            inner: Arc::clone(&self.provider),
+            calls: Arc::clone(api_calls),
+            budget: Arc::clone(budget),
+        });
 
         // iterate over edges in batch
         for &(proposer_id, opposer_id) in pairs {
-            let max_turns = self.max_turns;
+            // shared across tasks, drawn in batch order so a seed reproduces the openers
+            let config = if self.randomize_opening && rng.random_bool(0.5) {
+                Arc::clone(&flipped)
+            } else {
+                Arc::clone(&config)
+            };
+            let provider = Arc::clone(&counted);
 
-            // clone none copy types
-            let topic = self.topic.clone();
-            let judge_model = self.judge_model.clone();
+            // get agents from registry
+            let proposer = debater(registry, proposer_id);
+            let opposer = debater(registry, opposer_id);
+            let pairing = format!(
+                "debate {} ({}) vs {} ({}) failed",
+                proposer_id, proposer.model, opposer_id, opposer.model
+            );
+            let debate_timeout = self.debate_timeout;
+            let span = tracing::info_span!(
+                "debate",
+                proposer_id,
+                opposer_id,
+                proposer_model = %proposer.model,
+                opposer_model = %opposer.model,
+            );
+            self.emit(SimulationEvent::DebateStarted {
+                proposer_id,
+                opposer_id,
+            });
 
-            // get agents model from registry
-            let proposer_model = registry.get_agent(proposer_id).unwrap().model.clone();
-            let opposer_model = registry.get_agent(opposer_id).unwrap().model.clone();
+            // a hit is the stored debate moved onto this edge. it cost nothing this time.
+            // undecided debates are never replayed, a continuation has to run again for real
+            let cached = self.cache.as_ref().and_then(|cache| {
+                let mut debate = cache
+                    .get(&self.cache_key(&proposer.model, &opposer.model))
+                    .filter(|debate| debate.outcome != DebateOutcome::Ongoing)?;
+                debate.proposer_id = proposer_id;
+                debate.opposer_id = opposer_id;
+                debate.usage = TokenUsage::default();
+                Some(debate)
+            });
 
             // spawn task for each edge
-            tasks.spawn(async move {
-                run_debate(
-                    proposer_id,
-                    opposer_id,
-                    &proposer_model,
-                    &opposer_model,
-                    &topic,
-                    max_turns,
-                    &judge_model,
-                )
-                .await
-            });
+            tasks.spawn(
+                async move {
+                    if let Some(debate) = cached {
+                        tracing::debug!("debate served from cache");
+                        return Ok((proposer_id, opposer_id, Some(debate)));
+                    }
+                    let debate = run_debate(provider.as_ref(), &proposer, &opposer, &config);
+
+                    // none when the debate timed out
+                    match debate_timeout {
+                        Some(limit) => match tokio::time::timeout(limit, debate).await {
+                            Ok(result) => result.map(Some),
+                            Err(_) => Ok(None),
+                        },
+                        None => debate.await.map(Some),
+                    }
+                    .map(|debate| (proposer_id, opposer_id, debate))
+                    .context(pairing)
+                }
+                .instrument(span),
+            );
         }
 
         // result vec for return
         let mut results = Vec::new();
+        let mut timed_out = Vec::new();
         // wait for any task to finish then
         while let Some(result) = tasks.join_next().await {
-            // result is Result<Result<Debate, anyhow::Error>, JoinError> here
-            results.push(result??);
+            // result is Result<Result<_, anyhow::Error>, JoinError> here. the first failure
+            // stops the batch, the other debates are aborted instead of running on unobserved
+            let outcome = match result {
+                Ok(Ok(outcome)) => outcome,
+                Ok(Err(err)) => {
+                    tasks.shutdown().await;
+                    return Err(err);
+                }
+                Err(err) => {
+                    tasks.shutdown().await;
+                    return Err(err.into());
+                }
+            };
+            match outcome {
+                (_, _, Some(debate)) => results.push(debate),
+                (proposer_id, opposer_id, None) => {
+                    tracing::warn!(proposer_id, opposer_id, "debate timed out");
+                    timed_out.push((proposer_id, opposer_id))
+                }
+            }
         }
+        timed_out.sort_unstable();
 
-        Ok(results)
+        // store the decided debates that ran, hits are in the cache already
+        if let Some(cache) = &self.cache {
+            let before = cache.len();
+            for debate in &results {
+                if debate.outcome == DebateOutcome::Ongoing {
+                    continue;
+                }
+                let key = self.cache_key(&debate.proposer_model, &debate.opposer_model);
+                if cache.get(&key).is_none() {
+                    cache.insert(key, debate.clone());
+                }
+            }
+            if cache.len() > before {
+                cache.save()?;
+            }
+        }
+
+        // tasks finish in any order, keep the batch order so runs are reproducible
+        results.sort_by_key(|debate| {
+            pairs
+                .iter()
+                .position(|&pair| pair == (debate.proposer_id, debate.opposer_id))
+        });
+
+        Ok((results, timed_out))
+    }
+
+    // coin flip stand-ins for a batch, drawn in batch order so a seed reproduces them
+    fn dry_debate_batch(
+        &self,
+        registry: &Registry,
+        pairs: &[(u32, u32)],
+        bias: f64,
+        rng: &mut StdRng,
+    ) -> Vec<Debate> {
+        pairs
+            .iter()
+            .map(|&(proposer_id, opposer_id)| {
+                self.emit(SimulationEvent::DebateStarted {
+                    proposer_id,
+                    opposer_id,
+                });
+                let proposer = debater(registry, proposer_id);
+                let opposer = debater(registry, opposer_id);
+                let chance = self.dry_win_chance(&proposer.model, &opposer.model, bias);
+                let mut debate = Debate::new(proposer_id, opposer_id, self.max_turns)
+                    .with_topic(&self.topic)
+                    .with_models(proposer.model, opposer.model);
+                debate.set_outcome(if rng.random_bool(chance) {
+                    DebateOutcome::ProposerWon
+                } else {
+                    DebateOutcome::OpposerWon
+                });
+                debate
+            })
+            .collect()
+    }
+
+    // chance the proposer wins a dry debate, see model_strengths
+    fn dry_win_chance(&self, proposer_model: &str, opposer_model: &str, bias: f64) -> f64 {
+        if self.model_strengths.is_empty() {
+            return bias;
+        }
+        let strength = |model: &str| self.model_strengths.get(model).copied().unwrap_or(0.0);
+        (0.5 + (strength(proposer_model) - strength(opposer_model)) / 2.0).clamp(0.0, 1.0)
     }
 
     fn apply_batch_results(
         &self,
         registry: &mut Registry,
         debates: Vec<Debate>,
-        infected_deque: &mut VecDeque<u32>,
-        all_debates: &mut Vec<Debate>,
+        state: &mut RunState,
     ) -> Result<()> {
         for debate in debates.into_iter() {
+            // a winning opposer can still be persuaded, the debate keeps the judge's verdict
+            let outcome = if debate.outcome == DebateOutcome::OpposerWon
+                && self.infection_probability > 0.0
+                && state.rng.random_bool(self.infection_probability)
+            {
+                DebateOutcome::ProposerWon
+            } else {
+                debate.outcome
+            };
+
             // update registry of outcome, this changes infectionstatus on agents
-            registry.apply_debate_outcome(debate.proposer_id, debate.opposer_id, debate.outcome)?;
+            registry.apply_debate_outcome(debate.proposer_id, debate.opposer_id, outcome)?;
+            match outcome {
+                DebateOutcome::ProposerWon => {
+                    tracing::info!(
+                        agent_id = debate.opposer_id,
+                        infected_by = debate.proposer_id,
+                        "agent infected"
+                    );
+                    self.emit(SimulationEvent::AgentInfected {
+                        agent_id: debate.opposer_id,
+                        infected_by: debate.proposer_id,
+                    })
+                }
+                DebateOutcome::OpposerWon => {
+                    tracing::info!(agent_id = debate.opposer_id, "agent immune");
+                    self.emit(SimulationEvent::AgentImmune {
+                        agent_id: debate.opposer_id,
+                    })
+                }
+                DebateOutcome::Ongoing => {
+                    // unvisit the edge so a later batch debates it again
+                    let edge = (debate.proposer_id, debate.opposer_id);
+                    let continued = state.continuations.entry(edge).or_insert(0);
+                    if *continued < self.max_continuations {
+                        *continued += 1;
+                        state.visited_edges.remove(&edge);
+                        tracing::debug!(
+                            agent_id = debate.opposer_id,
+                            "debate undecided, re-queued"
+                        );
+                    } else {
+                        tracing::debug!(agent_id = debate.opposer_id, "debate undecided");
+                    }
+                }
+            }
+
+            if outcome == DebateOutcome::ProposerWon {
+                state
+                    .transmissions
+                    .entry(debate.proposer_id)
+                    .or_default()
+                    .push(debate.opposer_id);
+            }
 
             // if agent lost debate (becomes infected) and not already in infected_deque, add to fontier
-            if debate.outcome == DebateOutcome::ProposerWon
-                && !infected_deque.contains(&debate.opposer_id)
+            if outcome == DebateOutcome::ProposerWon
+                && !state.infected_deque.contains(&debate.opposer_id)
             {
-                infected_deque.push_back(debate.opposer_id);
+                state.infected_deque.push_back(debate.opposer_id);
             }
 
-            // push debate
-            all_debates.push(debate);
+            // push debate, both agents keep their own copy
+            registry.record_debate(&debate)?;
+            if self.events.is_some() {
+                self.emit(SimulationEvent::DebateFinished {
+                    debate: Box::new(debate.clone()),
+                    stats: registry.get_statistics(),
+                });
+            }
+            state.debates.push(debate);
         }
 
         Ok(())
     }
 
-    fn finalize(&self, registry: &Registry, debates: Vec<Debate>) -> SimulationResult {
+    fn finalize(
+        &self,
+        registry: &Registry,
+        state: RunState,
+        terminated_reason: TerminationReason,
+    ) -> SimulationResult {
         let stats = registry.get_statistics();
 
+        let mut usage = TokenUsage::default();
+        for debate in &state.debates {
+            usage += debate.usage;
+        }
+
         SimulationResult {
-            rounds: debates.len(),
+            topic: self.topic.clone(),
+            rounds: state.debates.len(),
             total_agents: stats.total_agents,
             infected: stats.infected_agents,
             healthy: stats.healthy_agents,
             immune: stats.immune_agents,
-            debates,
+            debates: state.debates,
+            timed_out: state.timed_out,
+            timeline: state.timeline,
+            snapshots: state.snapshots,
+            patient_zeros: state.patient_zeros,
+            transmissions: state.transmissions,
+            usage,
+            api_calls: state.api_calls.load(Ordering::SeqCst),
+            terminated_reason,
+            wall_time: state.started.elapsed(),
+        }
+    }
+}
+
+// every agent's status, one frame of SimulationResult::snapshots
+pub(crate) fn snapshot(registry: &Registry) -> HashMap<u32, InfectionStatus> {
+    registry
+        .get_all_agents()
+        .into_iter()
+        .map(|agent| (agent.id, agent.infection_status))
+        .collect()
+}
+
+// model, params and persona of an agent without its debate history, all a debate needs
+fn debater(registry: &Registry, id: u32) -> Agent {
+    let agent = registry.get_agent(id).unwrap();
+    Agent {
+        persona: agent.persona.clone(),
+        ..Agent::new(agent.id, agent.model.clone()).with_gen_params(agent.gen_params)
+    }
+}
+
+// debates and provider calls a run may still spend under max_rounds and max_api_calls.
+// run_components hands one budget to every component so the caps hold for the whole run
+pub(super) struct Budget {
+    max_rounds: Option<usize>,
+    max_api_calls: Option<usize>,
+    calls_per_debate: usize,
+    // provider calls made so far, retries included
+    api_calls: AtomicUsize,
+    // debates done or running, and the calls set aside for batches still running
+    spent: Mutex<(usize, usize)>,
+}
+
+impl Budget {
+    pub(super) fn new(sim: &Simulation) -> Self {
+        Self {
+            max_rounds: sim.max_rounds,
+            max_api_calls: sim.max_api_calls,
+            calls_per_debate: sim.calls_per_debate().max(1),
+            api_calls: AtomicUsize::new(0),
+            spent: Mutex::new((0, 0)),
+        }
+    }
+
+    // debates that still fit and the cap that limits them, none when no cap is set
+    fn left(
+        &self,
+        (debates, reserved_calls): (usize, usize),
+    ) -> Option<(usize, TerminationReason)> {
+        let rounds = self.max_rounds.map(|max_rounds| {
+            (
+                max_rounds.saturating_sub(debates),
+                TerminationReason::MaxRounds,
+            )
+        });
+        let calls = self.max_api_calls.map(|max_api_calls| {
+            let used = self.api_calls.load(Ordering::SeqCst) + reserved_calls;
+            let left = max_api_calls.saturating_sub(used) / self.calls_per_debate;
+            (left, TerminationReason::MaxApiCalls)
+        });
+        match (rounds, calls) {
+            (Some(rounds), Some(calls)) if calls.0 < rounds.0 => Some(calls),
+            (rounds, calls) => rounds.or(calls),
+        }
+    }
+
+    // the cap that has run out, if any
+    pub(super) fn exhausted(&self) -> Option<TerminationReason> {
+        let spent = *self.spent.lock().unwrap();
+        self.left(spent)
+            .and_then(|(left, reason)| (left == 0).then_some(reason))
+    }
+
+    // sets aside room for up to wanted debates and returns how many fit, or the spent cap
+    pub(super) fn reserve(&self, wanted: usize) -> Result<usize, TerminationReason> {
+        let mut spent = self.spent.lock().unwrap();
+        let granted = match self.left(*spent) {
+            Some((0, reason)) => return Err(reason),
+            Some((left, _)) => wanted.min(left),
+            None => wanted,
+        };
+        spent.0 += granted;
+        spent.1 += granted * self.calls_per_debate;
+        Ok(granted)
+    }
+
+    // hands back a reservation once its batch is done, debates that timed out do not count
+    pub(super) fn release(&self, reserved: usize, completed: usize) {
+        let mut spent = self.spent.lock().unwrap();
+        spent.0 -= reserved - completed;
+        spent.1 -= reserved * self.calls_per_debate;
+    }
+}
+
+// counts every request on its way to the real provider, shared by all tasks of a run
+struct CountingProvider {
+    inner: Arc<dyn LlmProvider>,
+    calls: Arc<AtomicUsize>,
+    budget: Arc<Budget>,
+}
+
+impl LlmProvider for CountingProvider {
+    fn complete<'a>(
+        &'a self,
+        model: &'a str,
+        messages: &'a [ChatMessage],
+        params: &'a GenerationParams,
+    ) -> BoxFuture<'a, Result<Completion>> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        self.budget.api_calls.fetch_add(1, Ordering::SeqCst);
+        self.inner.complete(model, messages, params)
+    }
+}
+
+// bookkeeping for a single run
+struct RunState {
+    // infected agents that may still have targets
+    infected_deque: VecDeque<u32>,
+    // every edge already debated (or timed out) is never scheduled again, so the run
+    // always terminates after at most one debate per edge, plus continuations
+    visited_edges: HashSet<(u32, u32)>,
+    // times an undecided edge was re-queued, see max_continuations
+    continuations: HashMap<(u32, u32), usize>,
+    debates: Vec<Debate>,
+    timed_out: Vec<(u32, u32)>,
+    timeline: Vec<RegistryStatistics>,
+    snapshots: Vec<HashMap<u32, InfectionStatus>>,
+    // drives infection_probability
+    rng: StdRng,
+    // provider calls made so far, see max_api_calls
+    api_calls: Arc<AtomicUsize>,
+    patient_zeros: Vec<u32>,
+    transmissions: HashMap<u32, Vec<u32>>,
+    started: Instant,
+}
+
+impl RunState {
+    fn new(patient_zeros: Vec<u32>, rng: StdRng) -> Self {
+        Self {
+            infected_deque: patient_zeros.iter().copied().collect(),
+            patient_zeros,
+            transmissions: HashMap::new(),
+            visited_edges: HashSet::new(),
+            continuations: HashMap::new(),
+            debates: Vec::new(),
+            timed_out: Vec::new(),
+            timeline: Vec::new(),
+            snapshots: Vec::new(),
+            rng,
+            api_calls: Arc::new(AtomicUsize::new(0)),
+            started: Instant::now(),
         }
     }
 }
 
+// why a run stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminationReason {
+    // no healthy agent left
+    Saturated,
+    // healthy agents remain but no infected agent has an undebated edge to them,
+    // e.g. a disconnected component or a wall of immune agents
+    NoReachableTargets,
+    // stopped by the max_rounds cap
+    MaxRounds,
+    // the next debate would not fit in max_api_calls
+    MaxApiCalls,
+    // the cancel flag was set
+    Cancelled,
+}
+
 /// Returned to callers (app crates)
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SimulationResult {
+    pub topic: String,
     pub rounds: usize,
     pub total_agents: usize,
     pub infected: usize,
     pub healthy: usize,
     pub immune: usize,
     pub debates: Vec<Debate>,
+    // (proposer, opposer) edges skipped because the debate hit the timeout
+    pub timed_out: Vec<(u32, u32)>,
+    // registry stats after each batch, for plotting growth over time
+    pub timeline: Vec<RegistryStatistics>,
+    // agent id -> status after each batch, only filled with with_snapshots(true)
+    pub snapshots: Vec<HashMap<u32, InfectionStatus>>,
+    // agents infected when the run started
+    pub patient_zeros: Vec<u32>,
+    // infector -> agents it infected during the run, in infection order
+    pub transmissions: HashMap<u32, Vec<u32>>,
+    // tokens spent across all completed debates
+    pub usage: TokenUsage,
+    // provider requests made, retries included
+    pub api_calls: usize,
+    pub terminated_reason: TerminationReason,
+    // how long the whole run took, per message latencies are on each Message
+    pub wall_time: Duration,
 }
 
 impl SimulationResult {
+    pub fn total_tokens(&self) -> u64 {
+        self.usage.total()
+    }
+
+    // basic reproduction number estimate, secondary infections per spreader where spreaders are
+    // the patient zeros plus every agent that infected someone. agents infected late that never
+    // got to spread are left out, so a chain scores 1.0 and a star scores its number of spokes.
+    // below 1.0 the outbreak fizzled out
+    pub fn r0(&self) -> f64 {
+        let spreaders: HashSet<u32> = self
+            .patient_zeros
+            .iter()
+            .chain(self.transmissions.keys())
+            .copied()
+            .collect();
+        if spreaders.is_empty() {
+            return 0.0;
+        }
+
+        let secondary: usize = self.transmissions.values().map(Vec::len).sum();
+        secondary as f64 / spreaders.len() as f64
+    }
+
     pub fn infection_rate(&self) -> f64 {
         if self.total_agents == 0 {
             0.0
@@ -210,4 +1127,965 @@ impl SimulationResult {
             self.healthy as f64 / self.total_agents as f64
         }
     }
+
+    // share of each model's debates it ended up on the winning side of, from either seat.
+    // undecided debates count as not won
+    pub fn model_win_rates(&self) -> HashMap<String, f64> {
+        // model -> (won, debated)
+        let mut tally: HashMap<&str, (usize, usize)> = HashMap::new();
+        for debate in &self.debates {
+            let seats = [
+                (&debate.proposer_model, DebateOutcome::ProposerWon),
+                (&debate.opposer_model, DebateOutcome::OpposerWon),
+            ];
+            for (model, winning_outcome) in seats {
+                let entry = tally.entry(model.as_str()).or_default();
+                entry.1 += 1;
+                if debate.outcome == winning_outcome {
+                    entry.0 += 1;
+                }
+            }
+        }
+
+        tally
+            .into_iter()
+            .map(|(model, (won, debated))| (model.to_string(), won as f64 / debated as f64))
+            .collect()
+    }
+
+    // one readable debate_{index}_{proposer}_{opposer}.txt per debate in run order, dir is
+    // created if missing. the index keeps continuations of an edge from overwriting each other
+    pub fn write_transcripts(&self, dir: &Path) -> Result<()> {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create transcript dir {}", dir.display()))?;
+
+        for (index, debate) in self.debates.iter().enumerate() {
+            let path = dir.join(format!(
+                "debate_{}_{}_{}.txt",
+                index, debate.proposer_id, debate.opposer_id
+            ));
+            fs::write(&path, debate.format_transcript())
+                .with_context(|| format!("Failed to write transcript {}", path.display()))?;
+        }
+
+        Ok(())
+    }
+
+    // one row per agent of the registry the run finished with, for spreadsheets.
+    // infected_by is empty for agents nobody infected
+    pub fn to_csv(&self, registry: &Registry) -> String {
+        let mut csv = String::from("id,model,status,infected_by,direct_infections\n");
+        for agent in registry.get_all_agents() {
+            let infected_by = agent.infected_by.map(|id| id.to_string());
+            csv.push_str(&format!(
+                "{},{},{},{},{}\n",
+                agent.id,
+                csv_field(&agent.model),
+                agent.infection_status,
+                infected_by.unwrap_or_default(),
+                registry.direct_infections(agent.id)
+            ));
+        }
+        csv
+    }
+}
+
+// quoted when it would otherwise break the row
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockProvider, block_on, serve_chat, text};
+    use core::{Topology, TopologyBuilder};
+    use genai::adapter::AdapterKind;
+    use genai::resolver::{AuthData, Endpoint, ServiceTargetResolver};
+    use genai::{ModelIden, ServiceTarget};
+
+    fn star_registry() -> Registry {
+        let mut registry = Registry::new();
+        for _ in 0..6 {
+            registry.create_agent("model".to_string());
+        }
+        registry.topology = Some(TopologyBuilder::star(2, &[0, 1, 3, 4, 5]));
+        registry.infect_patient_init(2).unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_build_debate_batch_is_deterministic() {
+        let sim = Simulation::new("topic", 1, "judge")
+            .with_parallelism(3)
+            .with_seed(7);
+
+        let first = star_registry();
+        let second = star_registry();
+        let deque = VecDeque::from(vec![2]);
+
+        let batch = sim.build_debate_batch(&first, &deque, &HashSet::new(), &mut sim.rng());
+        assert_eq!(batch, vec![(2, 0), (2, 1), (2, 3)]);
+        assert_eq!(
+            batch,
+            sim.build_debate_batch(&second, &deque, &HashSet::new(), &mut sim.rng())
+        );
+    }
+
+    #[test]
+    fn test_shuffled_batches_follow_the_seed() {
+        let mut registry = Registry::new();
+        for _ in 0..12 {
+            registry.create_agent("model".to_string());
+        }
+        registry.topology = Some(TopologyBuilder::star(0, &(1..12).collect::<Vec<u32>>()));
+        let deque = VecDeque::from(vec![0]);
+        let batch = |seed: u64| {
+            let sim = Simulation::new("topic", 1, "judge")
+                .with_parallelism(4)
+                .with_seed(seed)
+                .with_shuffled_batches(true);
+            sim.build_debate_batch(&registry, &deque, &HashSet::new(), &mut sim.rng())
+        };
+
+        assert_eq!(batch(7), batch(7));
+        assert_eq!(batch(7).len(), 4);
+        // 11 spokes, some seed leaves the lowest ids out of the batch
+        assert!((0..8).any(|seed| batch(seed) != vec![(0, 1), (0, 2), (0, 3), (0, 4)]));
+    }
+
+    #[test]
+    fn test_build_debate_batch_skips_visited_edges() {
+        let sim = Simulation::new("topic", 1, "judge").with_parallelism(3);
+        let registry = star_registry();
+        let deque = VecDeque::from(vec![2]);
+        let visited = HashSet::from([(2, 0), (2, 3)]);
+
+        let batch = sim.build_debate_batch(&registry, &deque, &visited, &mut sim.rng());
+        assert_eq!(batch, vec![(2, 1), (2, 4), (2, 5)]);
+    }
+
+    #[test]
+    fn test_run_with_mock_infects_star() {
+        let sim = Simulation::new("topic", 1, "judge")
+            .with_provider(MockProvider::judge_always("PROPOSITION"));
+        let mut registry = star_registry();
+
+        let result = block_on(sim.run(&mut registry)).unwrap();
+
+        assert_eq!(result.rounds, 5);
+        assert_eq!(result.infected, 6);
+        assert_eq!(result.terminated_reason, TerminationReason::Saturated);
+        assert_eq!(registry.get_agent(0).unwrap().infected_by, Some(2));
+
+        // the hub debated every spoke, each spoke only the hub
+        let debates_with = |id: u32| {
+            result
+                .debates
+                .iter()
+                .filter(|debate| debate.proposer_id == id || debate.opposer_id == id)
+                .count()
+        };
+        for id in 0..6 {
+            let history = &registry.get_agent(id).unwrap().debate_history;
+            assert_eq!(history.len(), debates_with(id));
+        }
+        assert_eq!(registry.get_agent(2).unwrap().debate_history.len(), 5);
+    }
+
+    #[test]
+    fn test_max_rounds_caps_debates() {
+        let sim = Simulation::new("topic", 1, "judge")
+            .with_parallelism(2)
+            .with_max_rounds(3)
+            .with_provider(MockProvider::judge_always("PROPOSITION"));
+        let mut registry = Registry::new();
+        for _ in 0..6 {
+            registry.create_agent("model".to_string());
+        }
+        registry.topology = Some(TopologyBuilder::ring(&[0, 1, 2, 3, 4, 5]));
+        registry.infect_patient_init(0).unwrap();
+
+        let result = block_on(sim.run(&mut registry)).unwrap();
+
+        // the second batch is cut down to the single debate left in the budget
+        assert_eq!(result.rounds, 3);
+        assert_eq!(result.infected, 4);
+        assert_eq!(result.terminated_reason, TerminationReason::MaxRounds);
+    }
+
+    #[test]
+    fn test_max_api_calls_is_never_exceeded() {
+        // 2 turns and 2 judges cost 6 calls per debate
+        let provider = Arc::new(MockProvider::judge_always("PROPOSITION"));
+        let sim = Simulation {
+            provider: provider.clone(),
+            ..Simulation::new("topic", 2, "judge")
+                .with_judges(["judge", "judge"])
+                .with_parallelism(4)
+                .with_max_api_calls(20)
+        };
+        let mut registry = star_registry();
+
+        let result = block_on(sim.run(&mut registry)).unwrap();
+
+        assert_eq!(result.rounds, 3);
+        assert_eq!(result.api_calls, 18);
+        assert_eq!(provider.calls().len(), result.api_calls);
+        assert_eq!(result.terminated_reason, TerminationReason::MaxApiCalls);
+    }
+
+    #[test]
+    fn test_events_report_every_debate() {
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let sim = Simulation::new("topic", 1, "judge")
+            .with_events(sender)
+            .with_provider(MockProvider::judge_always("PROPOSITION"));
+        let mut registry = star_registry();
+
+        let result = block_on(sim.run(&mut registry)).unwrap();
+        let mut events = Vec::new();
+        while let Ok(event) = receiver.try_recv() {
+            events.push(event);
+        }
+
+        let count =
+            |matches: fn(&SimulationEvent) -> bool| events.iter().filter(|e| matches(e)).count();
+        assert_eq!(
+            count(|e| matches!(e, SimulationEvent::DebateStarted { .. })),
+            5
+        );
+        assert_eq!(
+            count(|e| matches!(e, SimulationEvent::DebateFinished { .. })),
+            result.rounds
+        );
+        assert_eq!(
+            count(|e| matches!(e, SimulationEvent::AgentInfected { infected_by: 2, .. })),
+            5
+        );
+        assert_eq!(
+            count(|e| matches!(e, SimulationEvent::AgentImmune { .. })),
+            0
+        );
+
+        // the last debate sees the fully infected registry
+        match events.last() {
+            Some(SimulationEvent::DebateFinished { stats, .. }) => {
+                assert_eq!(stats.infected_agents, 6)
+            }
+            other => panic!("unexpected last event {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_progress_reported_after_every_batch() {
+        #[derive(Clone, Default)]
+        struct Recorder(Arc<std::sync::Mutex<Vec<(usize, usize)>>>);
+        impl ProgressReporter for Recorder {
+            fn on_progress(&self, done: usize, total_estimate: usize) {
+                self.0.lock().unwrap().push((done, total_estimate));
+            }
+        }
+
+        let recorder = Recorder::default();
+        let sim = Simulation::new("topic", 1, "judge")
+            .with_parallelism(2)
+            .with_progress(recorder.clone())
+            .with_provider(MockProvider::judge_always("PROPOSITION"));
+        let mut registry = star_registry();
+
+        let result = block_on(sim.run(&mut registry)).unwrap();
+
+        // 5 spokes in batches of 2
+        let reports = recorder.0.lock().unwrap().clone();
+        assert_eq!(result.timeline.len(), 3);
+        assert_eq!(reports, vec![(2, 5), (4, 5), (5, 5)]);
+    }
+
+    #[test]
+    fn test_snapshots_one_per_batch() {
+        let sim = Simulation::new("topic", 1, "judge")
+            .with_parallelism(2)
+            .with_provider(MockProvider::judge_always("PROPOSITION"));
+        let mut registry = star_registry();
+        let result = block_on(sim.run(&mut registry)).unwrap();
+        assert!(result.snapshots.is_empty());
+
+        let sim = sim.with_snapshots(true);
+        let mut registry = star_registry();
+        let result = block_on(sim.run(&mut registry)).unwrap();
+
+        // 5 spokes in batches of 2
+        assert_eq!(result.snapshots.len(), 3);
+        assert_eq!(result.snapshots.len(), result.timeline.len());
+        let infected = |frame: &HashMap<u32, InfectionStatus>| {
+            frame
+                .values()
+                .filter(|&&status| status == InfectionStatus::Infected)
+                .count()
+        };
+        assert_eq!(infected(&result.snapshots[0]), 3);
+        assert_eq!(infected(&result.snapshots[2]), 6);
+        assert_eq!(result.snapshots[2].len(), 6);
+    }
+
+    #[test]
+    fn test_with_client_uses_configured_endpoint() {
+        block_on(async {
+            let (base_url, hits) = serve_chat("WINNER: PROPOSITION").await;
+            let resolver = ServiceTargetResolver::from_resolver_fn(
+                move |target: ServiceTarget| -> Result<ServiceTarget, genai::resolver::Error> {
+                    Ok(ServiceTarget {
+                        endpoint: Endpoint::from_owned(base_url.clone()),
+                        auth: AuthData::from_single("local"),
+                        model: ModelIden::new(AdapterKind::Ollama, target.model.model_name),
+                    })
+                },
+            );
+            let client = Client::builder()
+                .with_service_target_resolver(resolver)
+                .build();
+
+            let mut registry = Registry::new();
+            registry.create_agent("llama3".to_string());
+            registry.create_agent("llama3".to_string());
+            registry.topology = Some(TopologyBuilder::fully_connected(&[0, 1]));
+            registry.infect_patient_init(0).unwrap();
+            let sim = Simulation::new("topic", 1, "llama3").with_client(client);
+
+            let result = sim.run(&mut registry).await.unwrap();
+
+            // two debater turns and one judge
+            assert_eq!(hits.load(Ordering::SeqCst), 3);
+            assert_eq!(result.infected, 2);
+            assert_eq!(result.usage.prompt_tokens, 9);
+        });
+    }
+
+    #[test]
+    fn test_cancel_keeps_first_batch() {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&cancel);
+        // stop is pressed while the first batch is still running
+        let provider = MockProvider::new(move |model, _| {
+            flag.store(true, Ordering::SeqCst);
+            Ok(if model == "judge" {
+                "WINNER: PROPOSITION".to_string()
+            } else {
+                "argument".to_string()
+            })
+        });
+        let sim = Simulation::new("topic", 1, "judge")
+            .with_parallelism(2)
+            .with_cancellation(cancel)
+            .with_provider(provider);
+        let mut registry = star_registry();
+
+        let result = block_on(sim.run(&mut registry)).unwrap();
+
+        assert_eq!(result.rounds, 2);
+        assert_eq!(result.infected, 3);
+        assert_eq!(result.terminated_reason, TerminationReason::Cancelled);
+    }
+
+    #[test]
+    fn test_model_win_rates() {
+        let sim = Simulation::new("topic", 1, "judge")
+            .with_provider(MockProvider::judge_always("PROPOSITION"));
+        let mut registry = Registry::new();
+        for model in ["weak", "weak", "strong", "strong", "weak", "weak"] {
+            registry.create_agent(model.to_string());
+        }
+        registry.topology = Some(TopologyBuilder::star(2, &[0, 1, 3, 4, 5]));
+        registry.infect_patient_init(2).unwrap();
+
+        let result = block_on(sim.run(&mut registry)).unwrap();
+        let rates = result.model_win_rates();
+
+        // the strong hub wins all 5 debates, the strong spoke loses its only one
+        assert_eq!(rates.len(), 2);
+        assert_eq!(rates["strong"], 5.0 / 6.0);
+        assert_eq!(rates["weak"], 0.0);
+    }
+
+    #[test]
+    fn test_reinfection_rule() {
+        let run = |can_reinfect_immune| {
+            let sim = Simulation::new("topic", 1, "judge")
+                .with_reinfection(can_reinfect_immune)
+                .with_provider(MockProvider::judge_always("PROPOSITION"));
+            let mut registry = Registry::new();
+            for _ in 0..3 {
+                registry.create_agent("model".to_string());
+            }
+            registry.topology = Some(TopologyBuilder::fully_connected(&[0, 1, 2]));
+            registry.get_agent_mut(1).unwrap().infection_status = InfectionStatus::Immune;
+            registry.infect_patient_init(0).unwrap();
+
+            let result = block_on(sim.run(&mut registry)).unwrap();
+            (result.infected, result.immune)
+        };
+
+        // immune agent 1 survives by default and falls once it can be re-challenged
+        assert_eq!(run(false), (2, 1));
+        assert_eq!(run(true), (3, 0));
+    }
+
+    #[test]
+    fn test_randomized_opening_is_reproducible() {
+        let openers = |seed| {
+            let sim = Simulation::new("topic", 1, "judge")
+                .with_seed(seed)
+                .with_randomized_opening(true)
+                .with_provider(MockProvider::judge_always("PROPOSITION"));
+            let mut registry = star_registry();
+            let result = block_on(sim.run(&mut registry)).unwrap();
+            result
+                .debates
+                .iter()
+                .map(|debate| debate.opposer_opened)
+                .collect::<Vec<_>>()
+        };
+
+        let first = openers(11);
+        assert_eq!(first, openers(11));
+        // both sides get to open across the run
+        assert!(first.contains(&true) && first.contains(&false));
+    }
+
+    #[test]
+    fn test_run_topics_restarts_from_patient_zero() {
+        // the judge only sides with the proposer on pizza
+        let provider = MockProvider::new(|model, messages| {
+            if model != "judge" {
+                return Ok("argument".to_string());
+            }
+            if text(&messages[1]).contains("pizza") {
+                Ok("WINNER: PROPOSITION".to_string())
+            } else {
+                Ok("WINNER: OPPOSITION".to_string())
+            }
+        });
+        let sim = Simulation::new("unused", 1, "judge").with_provider(provider);
+        let mut registry = star_registry();
+        let topics = ["pineapple on pizza".to_string(), "AI safety".to_string()];
+
+        let results = block_on(sim.run_topics(&mut registry, &topics)).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].topic, "pineapple on pizza");
+        assert_eq!(results[0].infected, 6);
+        assert_eq!(results[1].topic, "AI safety");
+        assert_eq!((results[1].infected, results[1].immune), (1, 5));
+        assert_eq!(results[1].debates[0].topic, "AI safety");
+        // the registry is left as the last topic ended
+        assert!(registry.get_agent(2).unwrap().is_infected());
+        assert_eq!(registry.get_agent(2).unwrap().debate_history.len(), 5);
+    }
+
+    #[test]
+    fn test_undecided_debates_continue_up_to_the_cap() {
+        // judge_b only sides with the proposer from its third vote on, split votes are a tie
+        let run = |max_continuations: usize| {
+            let votes = AtomicUsize::new(0);
+            let provider = MockProvider::new(move |model, _| match model {
+                "judge_a" => Ok("WINNER: PROPOSITION".to_string()),
+                "judge_b" if votes.fetch_add(1, Ordering::SeqCst) < 2 => {
+                    Ok("WINNER: OPPOSITION".to_string())
+                }
+                "judge_b" => Ok("WINNER: PROPOSITION".to_string()),
+                _ => Ok("argument".to_string()),
+            });
+            let sim = Simulation::new("topic", 1, "judge_a")
+                .with_judges(["judge_a", "judge_b"])
+                .with_max_continuations(max_continuations)
+                .with_provider(provider);
+            let mut registry = Registry::new();
+            registry.create_agent("model".to_string());
+            registry.create_agent("model".to_string());
+            registry.topology = Some(Topology::from_edges(&[(0, 1)]));
+            registry.infect_patient_init(0).unwrap();
+            let result = block_on(sim.run(&mut registry)).unwrap();
+            (result, registry)
+        };
+
+        // off, the tie is final
+        let (result, registry) = run(0);
+        assert_eq!(result.rounds, 1);
+        assert_eq!(result.debates[0].outcome, DebateOutcome::Ongoing);
+        assert!(registry.get_agent(1).unwrap().is_healthy());
+
+        // one retry is another tie and the cap stops it
+        let (result, registry) = run(1);
+        assert_eq!(result.rounds, 2);
+        assert!(registry.get_agent(1).unwrap().is_healthy());
+        assert_eq!(
+            result.terminated_reason,
+            TerminationReason::NoReachableTargets
+        );
+
+        let (result, registry) = run(5);
+        assert_eq!(result.rounds, 3);
+        assert_eq!(result.debates[2].outcome, DebateOutcome::ProposerWon);
+        assert!(registry.get_agent(1).unwrap().is_infected());
+        assert_eq!(registry.get_agent(1).unwrap().debate_history.len(), 3);
+    }
+
+    #[test]
+    fn test_cache_hit_skips_the_provider() {
+        let path = std::env::temp_dir().join(format!("sim_cache_{}.json", std::process::id()));
+        let pair = || {
+            let mut registry = Registry::new();
+            registry.create_agent("a".to_string());
+            registry.create_agent("b".to_string());
+            registry.topology = Some(Topology::from_edges(&[(0, 1)]));
+            registry.infect_patient_init(0).unwrap();
+            registry
+        };
+
+        let first = Arc::new(MockProvider::judge_always("PROPOSITION"));
+        let sim = Simulation {
+            provider: first.clone(),
+            ..Simulation::new("topic", 1, "judge")
+        }
+        .with_cache(&path)
+        .unwrap();
+        let ran = block_on(sim.run(&mut pair())).unwrap();
+        assert_eq!(first.calls().len(), 3);
+
+        let second = Arc::new(MockProvider::judge_always("OPPOSITION"));
+        let sim = Simulation {
+            provider: second.clone(),
+            ..Simulation::new("topic", 1, "judge")
+        }
+        .with_cache(&path)
+        .unwrap();
+        let mut registry = pair();
+        let replayed = block_on(sim.run(&mut registry)).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(second.calls().is_empty());
+        assert_eq!(replayed.api_calls, 0);
+        assert_eq!(replayed.total_tokens(), 0);
+        assert_eq!(replayed.debates[0].exchanges, ran.debates[0].exchanges);
+        // the stored verdict, not the new judge's
+        assert_eq!(replayed.debates[0].outcome, DebateOutcome::ProposerWon);
+        assert!(registry.get_agent(1).unwrap().is_infected());
+    }
+
+    #[test]
+    fn test_cache_never_replays_undecided_debates() {
+        let path =
+            std::env::temp_dir().join(format!("sim_cache_ongoing_{}.json", std::process::id()));
+        // a tie from the split judges until judge_b comes round on its third vote
+        let votes = AtomicUsize::new(0);
+        let provider = MockProvider::new(move |model, _| match model {
+            "judge_a" => Ok("WINNER: PROPOSITION".to_string()),
+            "judge_b" if votes.fetch_add(1, Ordering::SeqCst) < 2 => {
+                Ok("WINNER: OPPOSITION".to_string())
+            }
+            "judge_b" => Ok("WINNER: PROPOSITION".to_string()),
+            _ => Ok("argument".to_string()),
+        });
+        let sim = Simulation::new("topic", 1, "judge_a")
+            .with_judges(["judge_a", "judge_b"])
+            .with_max_continuations(5)
+            .with_provider(provider)
+            .with_cache(&path)
+            .unwrap();
+        let mut registry = Registry::new();
+        registry.create_agent("model".to_string());
+        registry.create_agent("model".to_string());
+        registry.topology = Some(Topology::from_edges(&[(0, 1)]));
+        registry.infect_patient_init(0).unwrap();
+
+        let result = block_on(sim.run(&mut registry)).unwrap();
+        let cached = DebateCache::open(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        // every continuation ran again instead of replaying the first tie
+        assert_eq!(result.rounds, 3);
+        assert_eq!(result.api_calls, 12);
+        assert_eq!(result.debates[2].outcome, DebateOutcome::ProposerWon);
+        assert!(registry.get_agent(1).unwrap().is_infected());
+        assert_eq!(cached.len(), 1);
+        assert_eq!(
+            cached
+                .get(&sim.cache_key("model", "model"))
+                .unwrap()
+                .outcome,
+            DebateOutcome::ProposerWon
+        );
+    }
+
+    #[test]
+    fn test_summarize_before_judge() {
+        let provider = Arc::new(MockProvider::judge_always("PROPOSITION"));
+        let sim = Simulation {
+            provider: provider.clone(),
+            ..Simulation::new("topic", 1, "judge")
+        }
+        .with_max_rounds(1)
+        .with_summarize_before_judge(true)
+        .with_summarizer_model("summarizer");
+        let mut registry = star_registry();
+
+        let result = block_on(sim.run(&mut registry)).unwrap();
+
+        let models: Vec<String> = provider.calls().into_iter().map(|call| call.0).collect();
+        assert_eq!(models, vec!["model", "model", "summarizer", "judge"]);
+        assert_eq!(result.api_calls, 4);
+        let summary = result.debates[0].summary.as_deref().unwrap();
+        assert!(text(&provider.calls()[3].1[1]).contains(summary));
+    }
+
+    #[test]
+    fn test_failed_debate_names_the_pairing() {
+        let sim =
+            Simulation::new("topic", 2, "judge").with_provider(MockProvider::new(|model, _| {
+                match model {
+                    "flaky" => Err(anyhow::anyhow!("No response from model")),
+                    "judge" => Ok("WINNER: PROPOSITION".to_string()),
+                    _ => Ok("argument".to_string()),
+                }
+            }));
+        let mut registry = star_registry();
+        registry.get_agent_mut(4).unwrap().model = "flaky".to_string();
+
+        let err = block_on(sim.run(&mut registry)).unwrap_err();
+
+        // the whole chain, outermost context first
+        assert_eq!(
+            format!("{:#}", err),
+            "debate 2 (model) vs 4 (flaky) failed: agent 4 (flaky) failed on turn 1: \
+             No response from model"
+        );
+        // nothing of the failed batch is applied
+        assert_eq!(registry.infected_count(), 1);
+    }
+
+    #[test]
+    fn test_run_needs_an_infected_agent() {
+        let sim = Simulation::new("topic", 1, "judge")
+            .with_provider(MockProvider::judge_always("PROPOSITION"));
+
+        let mut registry = star_registry();
+        registry.reset_infections();
+        let err = block_on(sim.run(&mut registry)).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<RegistryError>(),
+            Some(&RegistryError::NoInfectedAgents)
+        );
+
+        let err = block_on(sim.run_dry(&mut Registry::new(), 0)).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<RegistryError>(),
+            Some(&RegistryError::NoInfectedAgents)
+        );
+    }
+
+    #[test]
+    fn test_run_rejects_unknown_topology_ids() {
+        let sim = Simulation::new("topic", 1, "judge")
+            .with_provider(MockProvider::judge_always("PROPOSITION"));
+        let mut registry = star_registry();
+        registry.topology = Some(TopologyBuilder::star(2, &[0, 1, 99]));
+
+        let err = block_on(sim.run(&mut registry)).unwrap_err();
+
+        assert_eq!(
+            err.downcast_ref::<RegistryError>(),
+            Some(&RegistryError::UnknownTopologyAgents(vec![99]))
+        );
+    }
+
+    #[test]
+    fn test_run_dry_makes_no_calls() {
+        let provider = Arc::new(MockProvider::judge_always("PROPOSITION"));
+        let sim = Simulation {
+            provider: provider.clone(),
+            ..Simulation::new("topic", 1, "judge")
+        };
+
+        let run = |seed| {
+            let mut registry = star_registry();
+            let result = block_on(sim.run_dry(&mut registry, seed)).unwrap();
+            let outcomes: Vec<DebateOutcome> = result.debates.iter().map(|d| d.outcome).collect();
+            (result.infected + result.immune, outcomes)
+        };
+
+        let (reached, outcomes) = run(5);
+        assert_eq!(reached, 6);
+        assert_eq!(outcomes, run(5).1);
+        assert!(provider.calls().is_empty());
+
+        // a certain bias always infects
+        let mut registry = star_registry();
+        let result =
+            block_on(sim.clone().with_dry_run_bias(1.0).run_dry(&mut registry, 0)).unwrap();
+        assert_eq!(result.infected, 6);
+    }
+
+    #[test]
+    fn test_run_dry_stronger_model_always_wins() {
+        let star = |hub: &str, spoke: &str| {
+            let mut registry = Registry::new();
+            for id in 0..6 {
+                registry.create_agent(if id == 2 { hub } else { spoke }.to_string());
+            }
+            registry.topology = Some(TopologyBuilder::star(2, &[0, 1, 3, 4, 5]));
+            registry.infect_patient_init(2).unwrap();
+            registry
+        };
+        let strengths = HashMap::from([("strong".to_string(), 1.0), ("weak".to_string(), 0.0)]);
+        let sim = Simulation::new("topic", 1, "judge").with_model_strengths(strengths);
+
+        for seed in 0..5 {
+            let result = block_on(sim.run_dry(&mut star("strong", "weak"), seed)).unwrap();
+            assert!(
+                result
+                    .debates
+                    .iter()
+                    .all(|debate| debate.outcome == DebateOutcome::ProposerWon)
+            );
+            assert_eq!(result.infected, 6);
+
+            let result = block_on(sim.run_dry(&mut star("weak", "strong"), seed)).unwrap();
+            assert_eq!(result.infected, 1);
+            assert_eq!(result.immune, 5);
+        }
+    }
+
+    #[test]
+    fn test_r0() {
+        let run = |registry: &mut Registry| {
+            let sim = Simulation::new("topic", 1, "judge")
+                .with_provider(MockProvider::judge_always("PROPOSITION"));
+            block_on(sim.run(registry)).unwrap()
+        };
+
+        // a line where each agent infects exactly the next one
+        let mut chain = Registry::new();
+        for _ in 0..6 {
+            chain.create_agent("model".to_string());
+        }
+        let edges: Vec<(u32, u32)> = (0..5).map(|id| (id, id + 1)).collect();
+        chain.topology = Some(Topology::from_edges(&edges));
+        chain.infect_patient_init(0).unwrap();
+        let result = run(&mut chain);
+        assert_eq!(result.transmissions[&2], vec![3]);
+        assert_eq!(result.r0(), 1.0);
+
+        let result = run(&mut star_registry());
+        assert_eq!(result.transmissions[&2], vec![0, 1, 3, 4, 5]);
+        assert_eq!(result.r0(), 5.0);
+
+        // patient zero convinces nobody
+        let mut registry = star_registry();
+        let sim = Simulation::new("topic", 1, "judge")
+            .with_provider(MockProvider::judge_always("OPPOSITION"));
+        assert_eq!(block_on(sim.run(&mut registry)).unwrap().r0(), 0.0);
+    }
+
+    #[test]
+    fn test_disconnected_agent_stays_healthy() {
+        let sim = Simulation::new("topic", 1, "judge")
+            .with_provider(MockProvider::judge_always("PROPOSITION"));
+        let mut registry = Registry::new();
+        for _ in 0..4 {
+            registry.create_agent("model".to_string());
+        }
+        // 3 has no connections at all
+        registry.topology = Some(TopologyBuilder::ring(&[0, 1, 2]));
+        registry.infect_patient_init(0).unwrap();
+
+        let result = block_on(sim.run(&mut registry)).unwrap();
+
+        assert_eq!(result.infected, 3);
+        assert!(registry.get_agent(3).unwrap().is_healthy());
+        assert_eq!(
+            result.terminated_reason,
+            TerminationReason::NoReachableTargets
+        );
+    }
+
+    #[test]
+    fn test_vaccinated_center_blocks_spread() {
+        let sim = Simulation::new("topic", 1, "judge")
+            .with_provider(MockProvider::judge_always("PROPOSITION"));
+        let mut registry = Registry::new();
+        for _ in 0..6 {
+            registry.create_agent("model".to_string());
+        }
+        registry.topology = Some(TopologyBuilder::star(2, &[0, 1, 3, 4, 5]));
+        registry.vaccinate(&[2]).unwrap();
+        registry.infect_patient_init(0).unwrap();
+
+        let result = block_on(sim.run(&mut registry)).unwrap();
+
+        assert_eq!(result.rounds, 0);
+        assert_eq!(result.infected, 1);
+        assert_eq!(result.immune, 1);
+        assert_eq!(result.healthy, 4);
+    }
+
+    #[test]
+    fn test_run_terminates_when_nobody_is_infected() {
+        let sim = Simulation::new("topic", 1, "judge")
+            .with_parallelism(2)
+            .with_provider(MockProvider::judge_always("OPPOSITION"));
+        let mut registry = Registry::new();
+        for _ in 0..5 {
+            registry.create_agent("model".to_string());
+        }
+        registry.topology = Some(TopologyBuilder::fully_connected(&[0, 1, 2, 3, 4]));
+        registry.infect_patient_init(0).unwrap();
+
+        let result = block_on(sim.run(&mut registry)).unwrap();
+
+        // patient zero debates each neighbor once and loses every time
+        assert_eq!(result.rounds, 4);
+        assert!(result.rounds <= registry.topology.as_ref().unwrap().connection_count());
+        assert_eq!(result.infected, 1);
+        assert_eq!(result.immune, 4);
+    }
+
+    #[test]
+    fn test_infection_probability() {
+        let run = |probability| {
+            let sim = Simulation::new("topic", 1, "judge")
+                .with_seed(3)
+                .with_infection_probability(probability)
+                .with_provider(MockProvider::judge_always("OPPOSITION"));
+            let mut registry = star_registry();
+            let result = block_on(sim.run(&mut registry)).unwrap();
+            (result.infected, result.immune)
+        };
+
+        // zero keeps the judge's verdict, every spoke stays immune
+        assert_eq!(run(0.0), (1, 5));
+        // one infects every spoke whatever the judge said
+        assert_eq!(run(1.0), (6, 0));
+    }
+
+    #[test]
+    fn test_usage_aggregates_across_debates() {
+        let sim = Simulation::new("topic", 1, "judge").with_provider(
+            MockProvider::judge_always("PROPOSITION").with_usage(TokenUsage::new(10, 5)),
+        );
+        let mut registry = star_registry();
+
+        let result = block_on(sim.run(&mut registry)).unwrap();
+
+        // 5 debates of 2 debater messages and a judge call
+        assert_eq!(result.usage, TokenUsage::new(150, 75));
+        assert_eq!(result.total_tokens(), 225);
+    }
+
+    #[test]
+    fn test_recovery_after() {
+        let sim = Simulation::new("topic", 1, "judge")
+            .with_parallelism(2)
+            .with_recovery_after(1)
+            .with_provider(MockProvider::judge_always("OPPOSITION"));
+        let mut registry = star_registry();
+
+        let result = block_on(sim.run(&mut registry)).unwrap();
+
+        // patient zero recovers after its second batch, so agent 5 is never reached
+        assert_eq!(result.rounds, 4);
+        assert_eq!(result.infected, 0);
+        assert_eq!(registry.get_immune_agent_ids().len(), 2);
+        assert!(registry.get_agent(5).unwrap().is_healthy());
+        assert!(registry.get_agent(0).unwrap().is_healthy());
+    }
+
+    #[test]
+    fn test_timeline_infections_never_decrease() {
+        let sim = Simulation::new("topic", 1, "judge")
+            .with_parallelism(1)
+            .with_provider(MockProvider::judge_always("PROPOSITION"));
+        let mut registry = Registry::new();
+        for _ in 0..6 {
+            registry.create_agent("model".to_string());
+        }
+        registry.topology = Some(TopologyBuilder::ring(&[0, 1, 2, 3, 4, 5]));
+        registry.infect_patient_init(0).unwrap();
+
+        let result = block_on(sim.run(&mut registry)).unwrap();
+
+        // one debate per batch
+        assert_eq!(result.timeline.len(), result.rounds);
+        assert!(
+            result
+                .timeline
+                .windows(2)
+                .all(|w| w[0].infected_agents <= w[1].infected_agents)
+        );
+        assert_eq!(result.timeline.last().unwrap().infected_agents, 6);
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let sim = Simulation::new("topic", 1, "judge")
+            .with_provider(MockProvider::judge_always("PROPOSITION"));
+        let mut registry = star_registry();
+        registry.get_agent_mut(0).unwrap().model = "gpt-4, tuned".to_string();
+        let result = block_on(sim.run(&mut registry)).unwrap();
+
+        let csv = result.to_csv(&registry);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines.len(), registry.agent_count() + 1);
+        assert_eq!(lines[0], "id,model,status,infected_by,direct_infections");
+        assert_eq!(lines[1], "0,\"gpt-4, tuned\",Infected,2,0");
+        // patient zero was infected by nobody
+        assert_eq!(lines[3], "2,model,Infected,,5");
+    }
+
+    #[test]
+    fn test_write_transcripts() {
+        let result = SimulationResult {
+            topic: "topic".to_string(),
+            rounds: 3,
+            total_agents: 3,
+            infected: 2,
+            healthy: 0,
+            immune: 1,
+            // 0 - 1 was undecided the first time and debated again
+            debates: vec![
+                Debate::new(0, 1, 1),
+                Debate::new(0, 2, 1),
+                Debate::new(0, 1, 1),
+            ],
+            timed_out: Vec::new(),
+            timeline: Vec::new(),
+            snapshots: Vec::new(),
+            patient_zeros: vec![0],
+            transmissions: HashMap::new(),
+            usage: TokenUsage::default(),
+            api_calls: 0,
+            terminated_reason: TerminationReason::Saturated,
+            wall_time: Duration::ZERO,
+        };
+        let dir = std::env::temp_dir().join(format!("transcripts_{}", std::process::id()));
+
+        result.write_transcripts(&dir).unwrap();
+
+        let mut names: Vec<String> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            names,
+            vec!["debate_0_0_1.txt", "debate_1_0_2.txt", "debate_2_0_1.txt"]
+        );
+    }
 }
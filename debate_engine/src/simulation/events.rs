@@ -0,0 +1,122 @@
+use crate::{Simulation, SimulationResult};
+use core::{Debate, Registry, RegistryStatistics};
+use futures::Stream;
+use std::sync::Arc;
+use tokio::sync::mpsc::UnboundedReceiver;
+
+// progress reported while a simulation runs, see Simulation::with_events
+#[derive(Debug, Clone)]
+pub enum SimulationEvent {
+    DebateStarted {
+        proposer_id: u32,
+        opposer_id: u32,
+    },
+    // registry stats are taken after the outcome was applied
+    DebateFinished {
+        debate: Box<Debate>,
+        stats: RegistryStatistics,
+    },
+    AgentInfected {
+        agent_id: u32,
+        infected_by: u32,
+    },
+    AgentImmune {
+        agent_id: u32,
+    },
+    // last event of run_streaming, only one of these two is sent
+    Completed(Box<SimulationResult>),
+    Failed(Arc<anyhow::Error>),
+}
+
+// how far run_streaming got, the run future is dropped once it has finished
+enum Stage<F> {
+    Running(F, UnboundedReceiver<SimulationEvent>),
+    // events sent before the run finished are passed on before the result
+    Draining(UnboundedReceiver<SimulationEvent>, SimulationEvent),
+    Done,
+}
+
+impl Simulation {
+    // run as a stream of events ending in Completed or Failed, for async uis. the run only
+    // makes progress while the stream is polled. replaces any sender set with with_events
+    pub fn run_streaming<'a>(
+        &self,
+        registry: &'a mut Registry,
+    ) -> impl Stream<Item = SimulationEvent> + 'a {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        let sim = self.clone().with_events(sender);
+        // sim and with it the sender are dropped when the run finishes, which ends the receiver
+        let run = Box::pin(async move {
+            match sim.run(registry).await {
+                Ok(result) => SimulationEvent::Completed(Box::new(result)),
+                Err(err) => SimulationEvent::Failed(Arc::new(err)),
+            }
+        });
+
+        futures::stream::unfold(Stage::Running(run, receiver), |mut stage| async move {
+            loop {
+                stage = match stage {
+                    Stage::Running(mut run, mut receiver) => tokio::select! {
+                        biased;
+                        Some(event) = receiver.recv() => {
+                            return Some((event, Stage::Running(run, receiver)));
+                        }
+                        last = &mut run => Stage::Draining(receiver, last),
+                    },
+                    Stage::Draining(mut receiver, last) => {
+                        return match receiver.recv().await {
+                            Some(event) => Some((event, Stage::Draining(receiver, last))),
+                            None => Some((last, Stage::Done)),
+                        };
+                    }
+                    Stage::Done => return None,
+                };
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockProvider, block_on};
+    use core::TopologyBuilder;
+    use futures::StreamExt;
+
+    #[test]
+    fn test_run_streaming_ends_with_completed() {
+        let mut registry = Registry::new();
+        for _ in 0..4 {
+            registry.create_agent("model".to_string());
+        }
+        registry.topology = Some(TopologyBuilder::star(0, &[1, 2, 3]));
+        registry.infect_patient_init(0).unwrap();
+        let sim = Simulation::new("topic", 1, "judge")
+            .with_parallelism(2)
+            .with_provider(MockProvider::judge_always("PROPOSITION"));
+
+        let events: Vec<SimulationEvent> = block_on(async {
+            let mut stream = std::pin::pin!(sim.run_streaming(&mut registry));
+            let mut events = Vec::new();
+            while let Some(event) = stream.next().await {
+                events.push(event);
+            }
+            events
+        });
+
+        let finished = events
+            .iter()
+            .filter(|event| matches!(event, SimulationEvent::DebateFinished { .. }))
+            .count();
+        assert_eq!(finished, 3);
+        match events.last() {
+            Some(SimulationEvent::Completed(result)) => {
+                assert_eq!(result.rounds, 3);
+                assert_eq!(result.infected, 4);
+                assert_eq!(result.healthy, 0);
+            }
+            other => panic!("unexpected last event {:?}", other),
+        }
+        assert!(registry.get_agent(3).unwrap().is_infected());
+    }
+}
@@ -1 +1,7 @@
+pub mod cache;
+pub mod components;
+pub mod config;
 pub mod engine;
+pub mod events;
+pub mod progress;
+pub mod tournament;
@@ -0,0 +1,14 @@
+// called by Simulation::run after every batch, implement it with e.g. indicatif or a logger.
+// the total is re-estimated each time as the debates done so far plus the undebated edges
+// from the current frontier, it grows as new agents get infected
+pub trait ProgressReporter: Send + Sync {
+    fn on_progress(&self, done: usize, total_estimate: usize);
+}
+
+// the default, reports nothing
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoProgress;
+
+impl ProgressReporter for NoProgress {
+    fn on_progress(&self, _done: usize, _total_estimate: usize) {}
+}
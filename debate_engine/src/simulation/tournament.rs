@@ -0,0 +1,171 @@
+use crate::Simulation;
+use crate::simulation::engine::Budget;
+use anyhow::Result;
+use core::{Debate, DebateOutcome, Registry};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::AtomicUsize;
+
+// wins and losses of one agent across a tournament, undecided debates count as neither
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Standing {
+    pub agent_id: u32,
+    pub wins: usize,
+    pub losses: usize,
+}
+
+#[derive(Debug)]
+pub struct TournamentResult {
+    // most wins first, then fewest losses, then lowest id
+    pub leaderboard: Vec<Standing>,
+    pub debates: Vec<Debate>,
+    // (proposer, opposer) edges skipped because the debate hit the timeout
+    pub timed_out: Vec<(u32, u32)>,
+}
+
+impl Simulation {
+    // round robin over every connection regardless of infection status, the lower id (or the
+    // source of a directed edge) proposes. the registry is left untouched. max_rounds,
+    // max_api_calls and the cancel flag stop it early the same way they stop run, the
+    // leaderboard then only counts the debates that ran
+    pub async fn run_tournament(&self, registry: &Registry) -> Result<TournamentResult> {
+        let pairs = registry
+            .topology
+            .as_ref()
+            .map(|topology| topology.get_all_connections())
+            .unwrap_or_default();
+
+        let mut standings: HashMap<u32, Standing> = registry
+            .get_all_agent_ids()
+            .into_iter()
+            .map(|agent_id| {
+                (
+                    agent_id,
+                    Standing {
+                        agent_id,
+                        ..Standing::default()
+                    },
+                )
+            })
+            .collect();
+        let mut debates = Vec::new();
+        let mut timed_out = Vec::new();
+        let api_calls = Arc::new(AtomicUsize::new(0));
+        let budget = Arc::new(Budget::new(self));
+        let mut rng = self.rng();
+
+        for batch in pairs.chunks(self.max_parallel_debates.max(1)) {
+            if self.cancelled() {
+                break;
+            }
+            let Ok(reserved) = budget.reserve(batch.len()) else {
+                break;
+            };
+            let batch = &batch[..reserved];
+            let (results, skipped) = self
+                .run_debate_batch(registry, batch, &api_calls, &budget, &mut rng)
+                .await?;
+            budget.release(reserved, results.len());
+            timed_out.extend(skipped);
+
+            for debate in results {
+                let (winner, loser) = match debate.outcome {
+                    DebateOutcome::ProposerWon => (debate.proposer_id, debate.opposer_id),
+                    DebateOutcome::OpposerWon => (debate.opposer_id, debate.proposer_id),
+                    DebateOutcome::Ongoing => {
+                        debates.push(debate);
+                        continue;
+                    }
+                };
+                standings.entry(winner).or_default().wins += 1;
+                standings.entry(loser).or_default().losses += 1;
+                debates.push(debate);
+            }
+        }
+
+        let mut leaderboard: Vec<Standing> = standings.into_values().collect();
+        leaderboard.sort_by(|a, b| {
+            b.wins
+                .cmp(&a.wins)
+                .then(a.losses.cmp(&b.losses))
+                .then(a.agent_id.cmp(&b.agent_id))
+        });
+
+        Ok(TournamentResult {
+            leaderboard,
+            debates,
+            timed_out,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockProvider, block_on, text};
+    use core::TopologyBuilder;
+    use std::sync::atomic::AtomicBool;
+
+    #[test]
+    fn test_tournament_leaderboard() {
+        // the champ wins every debate it is in, otherwise the proposer wins
+        let provider = MockProvider::new(|model, messages| {
+            if model != "judge" {
+                return Ok(format!("argument from {}", model));
+            }
+            let transcript: String = messages.iter().map(text).collect();
+            if transcript.contains("OPPOSITION: argument from champ") {
+                Ok("WINNER: OPPOSITION".to_string())
+            } else {
+                Ok("WINNER: PROPOSITION".to_string())
+            }
+        });
+        let sim = Simulation::new("topic", 1, "judge").with_provider(provider);
+        let mut registry = Registry::new();
+        for model in ["model", "model", "model", "champ"] {
+            registry.create_agent(model.to_string());
+        }
+        registry.topology = Some(TopologyBuilder::fully_connected(&[0, 1, 2, 3]));
+
+        let result = block_on(sim.run_tournament(&registry)).unwrap();
+
+        assert_eq!(result.debates.len(), 6);
+        let order: Vec<u32> = result.leaderboard.iter().map(|s| s.agent_id).collect();
+        assert_eq!(order, vec![3, 0, 1, 2]);
+        assert_eq!(
+            result.leaderboard[0],
+            Standing {
+                agent_id: 3,
+                wins: 3,
+                losses: 0
+            }
+        );
+        // nobody was infected along the way
+        assert_eq!(registry.healthy_count(), 4);
+    }
+
+    #[test]
+    fn test_tournament_respects_budgets_and_cancel() {
+        let mut registry = Registry::new();
+        for _ in 0..4 {
+            registry.create_agent("model".to_string());
+        }
+        registry.topology = Some(TopologyBuilder::fully_connected(&[0, 1, 2, 3]));
+        let sim = Simulation::new("topic", 1, "judge")
+            .with_parallelism(4)
+            .with_provider(MockProvider::judge_always("PROPOSITION"));
+
+        // the second batch is cut to the one debate left
+        let result = block_on(sim.clone().with_max_rounds(5).run_tournament(&registry)).unwrap();
+        assert_eq!(result.debates.len(), 5);
+
+        // 3 calls per debate
+        let result = block_on(sim.clone().with_max_api_calls(7).run_tournament(&registry)).unwrap();
+        assert_eq!(result.debates.len(), 2);
+
+        let cancel = Arc::new(AtomicBool::new(true));
+        let result = block_on(sim.with_cancellation(cancel).run_tournament(&registry)).unwrap();
+        assert!(result.debates.is_empty());
+        assert!(result.leaderboard.iter().all(|standing| standing.wins == 0));
+    }
+}
@@ -0,0 +1,130 @@
+use crate::llm::{BoxFuture, Completion, LlmProvider};
+use anyhow::Result;
+use core::{GenerationParams, TokenUsage};
+use genai::chat::ChatMessage;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+// #[tokio::test] expands to paths through `core`, which this workspace shadows with its own crate
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+type Responder = Box<dyn Fn(&str, &[ChatMessage]) -> Result<String> + Send + Sync>;
+type Call = (String, Vec<ChatMessage>, GenerationParams);
+
+// canned llm backend, replies come from a closure and every call is recorded
+pub struct MockProvider {
+    respond: Responder,
+    calls: Mutex<Vec<Call>>,
+    // reported for every reply
+    usage: TokenUsage,
+}
+
+impl MockProvider {
+    pub fn new(
+        respond: impl Fn(&str, &[ChatMessage]) -> Result<String> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            respond: Box::new(respond),
+            calls: Mutex::new(Vec::new()),
+            usage: TokenUsage::default(),
+        }
+    }
+
+    pub fn with_usage(mut self, usage: TokenUsage) -> Self {
+        self.usage = usage;
+        self
+    }
+
+    // debaters argue, the model named "judge" always picks winner
+    pub fn judge_always(winner: &'static str) -> Self {
+        Self::new(move |model, _| {
+            if model == "judge" {
+                Ok(format!("WINNER: {}", winner))
+            } else {
+                Ok(format!("argument from {}", model))
+            }
+        })
+    }
+
+    pub fn calls(&self) -> Vec<Call> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+impl LlmProvider for MockProvider {
+    fn complete<'a>(
+        &'a self,
+        model: &'a str,
+        messages: &'a [ChatMessage],
+        params: &'a GenerationParams,
+    ) -> BoxFuture<'a, Result<Completion>> {
+        self.calls
+            .lock()
+            .unwrap()
+            .push((model.to_string(), messages.to_vec(), *params));
+        let usage = self.usage;
+        let response = (self.respond)(model, messages).map(|text| Completion { text, usage });
+        Box::pin(async move { response })
+    }
+}
+
+// local openai compatible endpoint answering every chat request with reply, returns its
+// base url and a count of the requests it served. must be called inside a runtime
+pub async fn serve_chat(reply: &'static str) -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}/v1/", listener.local_addr().unwrap());
+    let hits = Arc::new(AtomicUsize::new(0));
+
+    let served = Arc::clone(&hits);
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            // read the headers, then as much body as they announce
+            let mut request = Vec::new();
+            let mut buf = [0; 4096];
+            let body_start = loop {
+                let n = stream.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                if let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                    break end + 4;
+                }
+            };
+            let headers = String::from_utf8_lossy(&request[..body_start]).to_lowercase();
+            let length: usize = headers
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:"))
+                .map_or(0, |value| value.trim().parse().unwrap());
+            while request.len() < body_start + length {
+                let n = stream.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+            served.fetch_add(1, Ordering::SeqCst);
+
+            let body = format!(
+                r#"{{"id":"1","object":"chat.completion","created":0,"model":"local","choices":[{{"index":0,"message":{{"role":"assistant","content":"{}"}},"finish_reason":"stop"}}],"usage":{{"prompt_tokens":3,"completion_tokens":2,"total_tokens":5}}}}"#,
+                reply
+            );
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+
+    (base_url, hits)
+}
+
+// text of a chat message
+pub fn text(message: &ChatMessage) -> String {
+    message.content.joined_texts().unwrap_or_default()
+}
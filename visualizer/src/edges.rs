@@ -0,0 +1,84 @@
+use egui::epaint::ColorMode;
+use egui::{Color32, Pos2, Shape};
+use egui_graphs::{DefaultEdgeShape, DisplayEdge, DisplayNode, DrawContext, EdgeProps, Node};
+use petgraph::EdgeType;
+use petgraph::stable_graph::IndexType;
+
+// infection arrows stand out from the gray topology edges
+pub(crate) const INFECTION_COLOR: Color32 = Color32::from_rgb(230, 120, 0);
+
+// edge payload, topology connection or infected_by -> infected overlay
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EdgeKind {
+    Topology,
+    Infection,
+}
+
+// default egui_graphs edge, recolored for infections and hideable for the overlay toggle
+#[derive(Debug, Clone)]
+pub(crate) struct AgentEdgeShape {
+    inner: DefaultEdgeShape,
+    kind: EdgeKind,
+    pub hidden: bool,
+}
+
+impl From<EdgeProps<EdgeKind>> for AgentEdgeShape {
+    fn from(props: EdgeProps<EdgeKind>) -> Self {
+        Self {
+            kind: props.payload,
+            inner: DefaultEdgeShape::from(props),
+            hidden: false,
+        }
+    }
+}
+
+impl<N: Clone, Ty: EdgeType, Ix: IndexType, D: DisplayNode<N, EdgeKind, Ty, Ix>>
+    DisplayEdge<N, EdgeKind, Ty, Ix, D> for AgentEdgeShape
+{
+    fn shapes(
+        &mut self,
+        start: &Node<N, EdgeKind, Ty, Ix, D>,
+        end: &Node<N, EdgeKind, Ty, Ix, D>,
+        ctx: &DrawContext,
+    ) -> Vec<Shape> {
+        if self.hidden {
+            return Vec::new();
+        }
+
+        let mut shapes =
+            DisplayEdge::<N, EdgeKind, Ty, Ix, D>::shapes(&mut self.inner, start, end, ctx);
+        if self.kind == EdgeKind::Infection {
+            shapes
+                .iter_mut()
+                .for_each(|shape| recolor(shape, INFECTION_COLOR));
+        }
+        shapes
+    }
+
+    fn update(&mut self, state: &EdgeProps<EdgeKind>) {
+        DisplayEdge::<N, EdgeKind, Ty, Ix, D>::update(&mut self.inner, state);
+    }
+
+    fn is_inside(
+        &self,
+        start: &Node<N, EdgeKind, Ty, Ix, D>,
+        end: &Node<N, EdgeKind, Ty, Ix, D>,
+        pos: Pos2,
+    ) -> bool {
+        !self.hidden
+            && DisplayEdge::<N, EdgeKind, Ty, Ix, D>::is_inside(&self.inner, start, end, pos)
+    }
+}
+
+// lines, curves and arrow tips, labels keep their color
+fn recolor(shape: &mut Shape, color: Color32) {
+    match shape {
+        Shape::LineSegment { stroke, .. } => stroke.color = color,
+        Shape::CubicBezier(curve) => curve.stroke.color = ColorMode::Solid(color),
+        Shape::Path(path) => {
+            path.fill = color;
+            path.stroke.color = ColorMode::Solid(color);
+        }
+        _ => {}
+    }
+}
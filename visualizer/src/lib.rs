@@ -3,63 +3,154 @@ use core::Registry;
 use core::agent::{Agent, InfectionStatus};
 use eframe::{App, run_native};
 use egui::{Color32, Context};
-use egui_graphs::{Graph, default_edge_transform};
+use egui_graphs::{DefaultNodeShape, Graph, default_edge_transform};
 use petgraph::Directed;
-use petgraph::stable_graph::StableGraph;
+use petgraph::stable_graph::EdgeIndex;
+use std::collections::HashMap;
+
+mod edges;
+mod render;
+use edges::{AgentEdgeShape, EdgeKind, INFECTION_COLOR};
+pub use render::render_graph_to_png;
 
 // graph type to use agent as the payload
-type AgentGraph = Graph<Agent, (), Directed, u32>;
+type AgentGraph = Graph<Agent, EdgeKind, Directed, u32, DefaultNodeShape, AgentEdgeShape>;
+
+// what node colors encode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorScheme {
+    #[default]
+    Infection,
+    // one color per model, e.g. to check the hub runs the strong model
+    Model,
+}
 
-pub fn visualize_graph(registry: &Registry) -> Result<()> {
+pub fn visualize_graph(registry: &Registry, scheme: ColorScheme) -> Result<()> {
     struct GraphApp {
         g: AgentGraph,
+        // (label, color) pairs shown above the graph
+        legend: Vec<(String, Color32)>,
+        // infected_by -> infected arrows drawn over the topology
+        infection_edges: Vec<EdgeIndex<u32>>,
+        show_infections: bool,
     }
 
     impl GraphApp {
-        fn new(_cc: &eframe::CreationContext<'_>, registry: &Registry) -> Self {
-            let pet_graph = build_graph_from_registry(registry);
+        fn new(
+            _cc: &eframe::CreationContext<'_>,
+            registry: &Registry,
+            scheme: ColorScheme,
+        ) -> Self {
+            let mut pet_graph = registry
+                .to_petgraph()
+                .map(|_, agent| agent.clone(), |_, _| EdgeKind::Topology);
+            let model_colors = model_colors(registry);
+
+            // one arrow per transmission, reusing the node indices of the topology graph
+            let node_map: HashMap<u32, _> = pet_graph
+                .node_indices()
+                .map(|idx| (pet_graph[idx].id, idx))
+                .collect();
+            for agent in registry.get_all_agents() {
+                if let Some(infector) = agent.infected_by
+                    && let (Some(&from), Some(&to)) =
+                        (node_map.get(&infector), node_map.get(&agent.id))
+                {
+                    pet_graph.add_edge(from, to, EdgeKind::Infection);
+                }
+            }
 
             // map agent data to visual properties
             let g = egui_graphs::to_graph_custom(
                 &pet_graph,
-                |n| {
+                |n: &mut egui_graphs::Node<Agent, EdgeKind, Directed, u32, DefaultNodeShape>| {
                     // apply the default settings
                     egui_graphs::default_node_transform(n);
 
                     // get payload data
                     let agent = n.payload();
 
-                    // match infection for color
-                    let color = match agent.infection_status {
-                        InfectionStatus::Healthy => Color32::LIGHT_GRAY,
-                        InfectionStatus::Infected => Color32::DARK_RED,
-                        InfectionStatus::Immune => Color32::LIGHT_BLUE,
+                    // match infection or model for color
+                    let color = match scheme {
+                        ColorScheme::Infection => status_color(agent.infection_status),
+                        ColorScheme::Model => model_colors[&agent.model],
                     };
 
-                    // set label
-                    n.set_label(agent.id.to_string());
+                    // set label, patient zero is labelled and drawn larger
+                    if agent.is_infected() && agent.infected_by.is_none() {
+                        n.set_label(format!("{} (patient zero)", agent.id));
+                        n.display_mut().radius *= 1.6;
+                    } else {
+                        n.set_label(agent.id.to_string());
+                    }
                     // set color
                     n.set_color(color);
                 },
                 default_edge_transform,
             );
 
-            Self { g }
+            let legend = match scheme {
+                ColorScheme::Infection => [
+                    InfectionStatus::Healthy,
+                    InfectionStatus::Infected,
+                    InfectionStatus::Immune,
+                ]
+                .into_iter()
+                .map(|status| (status.to_string(), status_color(status)))
+                .collect(),
+                ColorScheme::Model => {
+                    let mut legend: Vec<(String, Color32)> = model_colors.into_iter().collect();
+                    legend.sort_by(|a, b| a.0.cmp(&b.0));
+                    legend
+                }
+            };
+
+            let infection_edges = g
+                .edges_iter()
+                .filter(|(_, edge)| *edge.payload() == EdgeKind::Infection)
+                .map(|(idx, _)| idx)
+                .collect();
+
+            Self {
+                g,
+                legend,
+                infection_edges,
+                show_infections: true,
+            }
         }
     }
 
     impl App for GraphApp {
         fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
+            egui::SidePanel::left("options").show(ctx, |ui| {
+                if ui
+                    .checkbox(&mut self.show_infections, "Show infections")
+                    .changed()
+                {
+                    for &idx in &self.infection_edges {
+                        if let Some(edge) = self.g.edge_mut(idx) {
+                            edge.display_mut().hidden = !self.show_infections;
+                        }
+                    }
+                }
+                ui.colored_label(INFECTION_COLOR, "→ infected by");
+            });
+
             egui::CentralPanel::default().show(ctx, |ui| {
                 ui.heading("Agent Network Visualization");
+                ui.horizontal_wrapped(|ui| {
+                    for (label, color) in &self.legend {
+                        ui.colored_label(*color, format!("● {}", label));
+                    }
+                });
                 // specify type
                 ui.add(&mut egui_graphs::GraphView::<
                     Agent,
-                    (),
+                    EdgeKind,
                     petgraph::Directed,
                     u32,
-                    egui_graphs::DefaultNodeShape,
-                    egui_graphs::DefaultEdgeShape,
+                    DefaultNodeShape,
+                    AgentEdgeShape,
                 >::new(&mut self.g));
             });
         }
@@ -74,34 +165,86 @@ pub fn visualize_graph(registry: &Registry) -> Result<()> {
     run_native(
         "Debate Simulation - Agent Network",
         native_options,
-        Box::new(|cc| Ok(Box::new(GraphApp::new(cc, registry)))),
+        Box::new(move |cc| Ok(Box::new(GraphApp::new(cc, registry, scheme)))),
     )
     .map_err(|e| anyhow::anyhow!("eframe error: {}", e))
 }
 
-fn build_graph_from_registry(registry: &Registry) -> StableGraph<Agent, ()> {
-    use std::collections::HashMap;
-    let mut g = StableGraph::new();
+// node color for each infection status, shared with the png renderer
+fn status_color(status: InfectionStatus) -> Color32 {
+    match status {
+        InfectionStatus::Healthy => Color32::LIGHT_GRAY,
+        InfectionStatus::Infected => Color32::DARK_RED,
+        InfectionStatus::Immune => Color32::LIGHT_BLUE,
+    }
+}
+
+// tab10, distinct enough for a handful of models
+const MODEL_PALETTE: [Color32; 10] = [
+    Color32::from_rgb(31, 119, 180),
+    Color32::from_rgb(255, 127, 14),
+    Color32::from_rgb(44, 160, 44),
+    Color32::from_rgb(214, 39, 40),
+    Color32::from_rgb(148, 103, 189),
+    Color32::from_rgb(140, 86, 75),
+    Color32::from_rgb(227, 119, 194),
+    Color32::from_rgb(127, 127, 127),
+    Color32::from_rgb(188, 189, 34),
+    Color32::from_rgb(23, 190, 207),
+];
 
-    // get all agents from the registry
-    let agents = registry.get_all_agents();
-    let mut node_map = HashMap::new();
+// each model name hashes to a palette slot, taken slots move on to the next free one so
+// models stay distinct until the palette runs out
+fn model_colors(registry: &Registry) -> HashMap<String, Color32> {
+    let mut models: Vec<&str> = registry
+        .get_all_agents()
+        .into_iter()
+        .map(|agent| agent.model.as_str())
+        .collect();
+    models.sort_unstable();
+    models.dedup();
 
-    // add nodes with agent payload
-    for agent in agents {
-        let id = agent.id;
-        let idx = g.add_node(agent.clone());
-        node_map.insert(id, idx);
+    let mut taken = [false; MODEL_PALETTE.len()];
+    let mut colors = HashMap::new();
+    for model in models {
+        let mut slot = (fnv1a(model) % MODEL_PALETTE.len() as u64) as usize;
+        if taken.iter().all(|&t| t) {
+            taken = [false; MODEL_PALETTE.len()];
+        }
+        while taken[slot] {
+            slot = (slot + 1) % MODEL_PALETTE.len();
+        }
+        taken[slot] = true;
+        colors.insert(model.to_string(), MODEL_PALETTE[slot]);
     }
 
-    // add connections
-    if let Some(topology) = &registry.topology {
-        for (from_id, to_id) in topology.get_all_connections() {
-            if let (Some(&f), Some(&t)) = (node_map.get(&from_id), node_map.get(&to_id)) {
-                g.add_edge(f, t, ());
-            }
+    colors
+}
+
+// stable across runs and rust versions, unlike DefaultHasher
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_colors_are_distinct_and_stable() {
+        let mut registry = Registry::new();
+        for model in ["gpt-4", "gpt-3.5-turbo", "gpt-4", "llama3"] {
+            registry.create_agent(model.to_string());
         }
-    }
 
-    g
+        let colors = model_colors(&registry);
+
+        assert_eq!(colors.len(), 3);
+        assert_ne!(colors["gpt-4"], colors["gpt-3.5-turbo"]);
+        assert_ne!(colors["gpt-4"], colors["llama3"]);
+        assert_ne!(colors["gpt-3.5-turbo"], colors["llama3"]);
+        assert_eq!(colors, model_colors(&registry));
+    }
 }
@@ -0,0 +1,120 @@
+use crate::status_color;
+use anyhow::{Context, Result};
+use core::Registry;
+use std::collections::HashMap;
+use std::f32::consts::TAU;
+use std::path::Path;
+use tiny_skia::{Color, FillRule, Paint, PathBuilder, Pixmap, Stroke, Transform};
+
+// draw the network to a png without opening a window, agents sit on a circle in id order
+pub fn render_graph_to_png(registry: &Registry, path: &Path, size: (u32, u32)) -> Result<()> {
+    let (width, height) = size;
+    let mut pixmap = Pixmap::new(width, height)
+        .with_context(|| format!("invalid image size {}x{}", width, height))?;
+    pixmap.fill(Color::WHITE);
+
+    let node_radius = (width.min(height) as f32 * 0.03).max(3.0);
+    let positions = circle_layout(registry, width as f32, height as f32, node_radius);
+
+    // edges first so nodes are drawn over them
+    let mut edge_paint = Paint::default();
+    edge_paint.set_color_rgba8(120, 120, 120, 255);
+    edge_paint.anti_alias = true;
+    let stroke = Stroke {
+        width: 1.5,
+        ..Stroke::default()
+    };
+
+    let (_, connections) = registry.get_graph_data();
+    for (from_id, to_id) in connections {
+        if let (Some(&(x1, y1)), Some(&(x2, y2))) = (positions.get(&from_id), positions.get(&to_id))
+        {
+            let mut builder = PathBuilder::new();
+            builder.move_to(x1, y1);
+            builder.line_to(x2, y2);
+            if let Some(line) = builder.finish() {
+                pixmap.stroke_path(&line, &edge_paint, &stroke, Transform::identity(), None);
+            }
+        }
+    }
+
+    // same colors as visualize_graph
+    for agent in registry.get_all_agents() {
+        let Some(&(x, y)) = positions.get(&agent.id) else {
+            continue;
+        };
+        let color = status_color(agent.infection_status);
+
+        let mut node_paint = Paint::default();
+        node_paint.set_color_rgba8(color.r(), color.g(), color.b(), 255);
+        node_paint.anti_alias = true;
+        if let Some(circle) = PathBuilder::from_circle(x, y, node_radius) {
+            pixmap.fill_path(
+                &circle,
+                &node_paint,
+                FillRule::Winding,
+                Transform::identity(),
+                None,
+            );
+        }
+    }
+
+    pixmap
+        .save_png(path)
+        .with_context(|| format!("failed to write {}", path.display()))
+}
+
+// evenly spaced on the largest circle that fits, ids sorted so renders are reproducible
+fn circle_layout(
+    registry: &Registry,
+    width: f32,
+    height: f32,
+    node_radius: f32,
+) -> HashMap<u32, (f32, f32)> {
+    let ids = registry.get_all_agent_ids();
+
+    let (center_x, center_y) = (width / 2.0, height / 2.0);
+    let radius = (width.min(height) / 2.0 - node_radius * 2.0).max(0.0);
+
+    ids.iter()
+        .enumerate()
+        .map(|(i, &id)| {
+            let angle = TAU * i as f32 / ids.len() as f32;
+            (
+                id,
+                (
+                    center_x + radius * angle.cos(),
+                    center_y + radius * angle.sin(),
+                ),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::TopologyBuilder;
+
+    #[test]
+    fn test_render_graph_to_png() {
+        let mut registry = Registry::new();
+        for _ in 0..4 {
+            registry.create_agent("model".to_string());
+        }
+        registry.topology = Some(TopologyBuilder::ring(&[0, 1, 2, 3]));
+        registry.infect_patient_init(0).unwrap();
+        let path = std::env::temp_dir().join(format!("graph_{}.png", std::process::id()));
+
+        render_graph_to_png(&registry, &path, (200, 120)).unwrap();
+
+        let image = Pixmap::load_png(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!((image.width(), image.height()), (200, 120));
+        // some pixel has the infected color
+        let infected = status_color(core::InfectionStatus::Infected);
+        assert!(image.pixels().iter().any(|pixel| {
+            (pixel.red(), pixel.green(), pixel.blue()) == (infected.r(), infected.g(), infected.b())
+        }));
+    }
+}